@@ -50,6 +50,40 @@ impl GrzResolvedSlide {
     }
 }
 
+impl GrzResolvedSlide {
+    /// Where the link under `pos` goes, which is either
+    /// a URL or a slide like `#intro`, given the same
+    /// `size` the slide is drawn with
+    pub fn link_at(&self, size: Rect, pos: Pos2) -> Option<Arc<str>> {
+        self.link_rects(size)
+            .into_iter()
+            .rev()
+            .find(|(rect, _)| rect.contains(pos))
+            .map(|(_, link)| link)
+    }
+
+    /// The areas the links on the slide cover, along with
+    /// where they go, given the size the slide is drawn with
+    pub fn link_rects(&self, size: Rect) -> Vec<(Rect, Arc<str>)> {
+        let (size, scale_factor) = get_size_and_factor(size);
+        self.objects
+            .values()
+            .flat_map(|object| object.link_rects(size, scale_factor))
+            .map(|(rect, link)| (rect, Arc::clone(link)))
+            .collect()
+    }
+
+    /// The areas the highlights on the slide cover,
+    /// given the size the slide is drawn with
+    pub fn highlight_rects(&self, size: Rect) -> Vec<Rect> {
+        let (size, scale_factor) = get_size_and_factor(size);
+        self.objects
+            .values()
+            .flat_map(|object| object.highlight_rects(size, scale_factor))
+            .collect()
+    }
+}
+
 impl GrzResolvedSlide {
     pub fn resolve_slide(
         root: &GrzRoot,
@@ -297,10 +331,14 @@ impl GrzResolvedSlide {
                     let mut name = smartstring::alias::String::from("__line__");
                     name.push_str(obj_names[0].as_str());
                     name.push_str(obj_names[1].as_str());
-                    let Some(first_obj) = objects.get(&obj_names[0]) else {
-                        continue;
-                    };
-                    let Some(second_obj) = objects.get(&obj_names[1]) else {
+                    let (Some(first_obj), Some(second_obj)) =
+                        (objects.get(&obj_names[0]), objects.get(&obj_names[1]))
+                    else {
+                        tracing::warn!(
+                            "Line between `{}` and `{}` skipped, both objects must be on the slide",
+                            obj_names[0],
+                            obj_names[1]
+                        );
                         continue;
                     };
                     objects.insert(
@@ -1,12 +1,12 @@
 use std::sync::Arc;
 
-use egui::{Align2, Color32, CornerRadius, Image, Rect, Stroke};
+use egui::{Align2, Color32, CornerRadius, CursorIcon, Image, Rect, Stroke};
 use egui_glyphon::{BufferWithTextArea, cosmic_text::fontdb::ID};
 use grezi_parser::{actions::SlideParams, slide::ObjState};
 use keyframe::EasingFunction;
 use smallvec::SmallVec;
 
-use crate::text::{ResolvedBuffer, ResolvedTextTag};
+use crate::text::{ResolvedBuffer, ResolvedTextTag, TextRule};
 
 #[derive(Clone, Copy)]
 pub struct ResolvedObjPositions {
@@ -48,6 +48,41 @@ impl ResolvedObject {
         }
     }
 
+    /// The areas the links in the object cover once it's done
+    /// moving, if it's a text object that stays on screen
+    pub fn link_rects(&self, size: Rect, scale_factor: f32) -> Vec<(Rect, &Arc<str>)> {
+        match &self.inner {
+            ResolvedObjInner::Text { job, .. }
+                if !matches!(self.params.state, ObjState::Exiting) =>
+            {
+                link_rects(
+                    job,
+                    scale_rect(self.params.max_pos, size, scale_factor),
+                    scale_factor,
+                )
+                .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// The areas a highlight covers once it's done
+    /// moving, if it stays on screen
+    pub fn highlight_rects(&self, size: Rect, scale_factor: f32) -> Vec<Rect> {
+        match &self.inner {
+            ResolvedObjInner::Highlight { rects, .. }
+                if !matches!(self.params.state, ObjState::Exiting) =>
+            {
+                let obj_pos = scale_rect(self.params.max_pos, size, scale_factor);
+                rects
+                    .iter()
+                    .map(|rect| (*rect * scale_factor).translate(obj_pos.min.to_vec2()))
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
     pub fn new_based_on_this(&self, inner: ResolvedObjInner) -> Self {
         Self {
             params: ResolvedObjPositions {
@@ -67,6 +102,9 @@ pub enum ResolvedObjInner {
         job: SmallVec<[ResolvedBuffer; 1]>,
         tags: SmallVec<[ResolvedTextTag; 3]>,
         fonts: SmallVec<[ID; 8]>,
+        /// Lines between the rows of tables
+        /// and the lines of formulas
+        rules: Vec<TextRule>,
     },
     Image {
         image: Image<'static>,
@@ -127,7 +165,19 @@ impl ResolvedObject {
         }
 
         match &self.inner {
-            ResolvedObjInner::Text { job, tags, .. } => {
+            ResolvedObjInner::Text {
+                job, tags, rules, ..
+            } => {
+                for rule in rules {
+                    ui.painter().line_segment(
+                        rule.points
+                            .map(|point| obj_pos.min + point.to_vec2() * scale_factor),
+                        Stroke::new(
+                            rule.width * scale_factor,
+                            rule.color.gamma_multiply(opacity),
+                        ),
+                    );
+                }
                 let buffer_tag_offset = buffers.len();
                 for buffer in job {
                     let buffer_rect =
@@ -157,6 +207,14 @@ impl ResolvedObject {
                 if let Some(buffer_tags) = buffer_tags {
                     buffer_tags.extend(tags.iter().map(|tag| tag.offset(buffer_tag_offset)));
                 }
+                let hovering_link = !matches!(self.params.state, ObjState::Exiting)
+                    && ui.ctx().pointer_hover_pos().is_some_and(|pointer| {
+                        link_rects(job, obj_pos, scale_factor)
+                            .any(|(rect, _)| rect.contains(pointer))
+                    });
+                if hovering_link {
+                    ui.ctx().set_cursor_icon(CursorIcon::PointingHand);
+                }
                 // ui.painter().rect_stroke(
                 //     obj_pos,
                 //     CornerRadius::default(),
@@ -276,6 +334,22 @@ impl ResolvedObject {
     }
 }
 
+/// The areas the links in the buffers of a text
+/// object drawn at `obj_pos` cover
+fn link_rects(
+    job: &[ResolvedBuffer],
+    obj_pos: Rect,
+    scale_factor: f32,
+) -> impl Iterator<Item = (Rect, &Arc<str>)> {
+    job.iter().flat_map(move |buffer| {
+        let buffer_min = obj_pos.min + buffer.buffer_rect.min.to_vec2() * scale_factor;
+        buffer
+            .link_rects()
+            .into_iter()
+            .map(move |(rect, link)| ((rect * scale_factor).translate(buffer_min.to_vec2()), link))
+    })
+}
+
 #[inline(always)]
 fn scale_rect(viewbox: Rect, size: Rect, factor: f32) -> Rect {
     (viewbox * factor).translate(size.min.to_vec2())
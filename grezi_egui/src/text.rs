@@ -1,14 +1,17 @@
 use std::{ops::Deref, sync::Arc};
 
-use egui::{Align2, Pos2, Rect, Vec2, mutex::RwLock};
+use egui::{Align2, Color32, Pos2, Rect, Vec2, mutex::RwLock};
 use egui_glyphon::{
     cosmic_text::{
         Align, Attrs, Buffer, CacheKeyFlags, Color, Cursor, Family, FeatureTag, FontFeatures,
-        FontSystem, Metrics, Stretch, Style, Weight, fontdb::ID,
+        FontSystem, LetterSpacing, Metrics, Stretch, Style, Weight, fontdb::ID,
     },
     measure_buffer,
 };
-use grezi_parser::text::{TextParagraph, TextSection, TextTag};
+use grezi_mathml::{MathLayout, MathMLDoc};
+use grezi_parser::text::{
+    Baseline, MATH_PLACEHOLDER, TextParagraph, TextSection, TextTable, TextTag,
+};
 use smallvec::SmallVec;
 use unicode_segmentation::UnicodeSegmentation;
 
@@ -17,6 +20,31 @@ use crate::object::ResolvedObjInner;
 const INDENT_AMOUNT: f32 = 75.0;
 const MARGIN_MULTIPLIER: f32 = 0.5;
 const LINE_HEIGHT_MULTIPLIER: f32 = 1.5;
+/// Width of the lines between the rows of a table
+const RULE_WIDTH: f32 = 2.0;
+/// The lowest bits of the metadata of a glyph hold the
+/// index of the link it's in plus one
+const LINK_METADATA: usize = (1 << 12) - 1;
+/// Above those, the spans that make room for formulas
+/// and scripts hold the index of the formula or script
+const INLINE_INDEX_SHIFT: u32 = 12;
+const INLINE_INDEX_METADATA: usize = LINK_METADATA << INLINE_INDEX_SHIFT;
+const MATH_METADATA: usize = 1 << 24;
+const SCRIPT_METADATA: usize = 1 << 25;
+const UNDERLINE_METADATA: usize = 1 << 26;
+const STRIKETHROUGH_METADATA: usize = 1 << 27;
+/// Size of subscripts and superscripts relative to the text around them
+const SCRIPT_SCALE: f32 = 0.7;
+/// How far subscripts are lowered, relative to the font size
+const SUBSCRIPT_SHIFT: f32 = 0.2;
+/// How far superscripts are raised, relative to the font size
+const SUPERSCRIPT_SHIFT: f32 = 0.4;
+/// Where underlines and strikethroughs go below the
+/// baseline, and how thick they are, relative to the
+/// font size
+const UNDERLINE_OFFSET: f32 = 0.15;
+const STRIKETHROUGH_OFFSET: f32 = -0.3;
+const DECORATION_WIDTH: f32 = 0.06;
 
 #[derive(Clone, Copy)]
 pub enum ResolvedTextTag {
@@ -35,6 +63,16 @@ pub enum ResolvedTextTag {
     Code(usize),
     Heading(u16, usize),
     Label(usize),
+    TableStart,
+    TableEnd,
+    TableRowStart,
+    TableRowEnd,
+    TableHeader(usize),
+    TableData(usize),
+    FormulaStart,
+    FormulaEnd,
+    MathRun(usize),
+    Script(usize),
 }
 
 impl ResolvedTextTag {
@@ -44,7 +82,11 @@ impl ResolvedTextTag {
             | Self::Untagged(buffer_index)
             | Self::Code(buffer_index)
             | Self::Heading(_, buffer_index)
-            | Self::Label(buffer_index) => *buffer_index += amount,
+            | Self::Label(buffer_index)
+            | Self::TableHeader(buffer_index)
+            | Self::TableData(buffer_index)
+            | Self::MathRun(buffer_index)
+            | Self::Script(buffer_index) => *buffer_index += amount,
             _ => {}
         }
 
@@ -58,6 +100,110 @@ pub struct ResolvedBuffer {
     /// Is the buffer a list number or
     /// bullet point
     pub marker: bool,
+    pub stacking: Stacking,
+    /// Lines of the formulas, underlines and strikethroughs
+    /// in the buffer, relative to the top left corner of
+    /// the buffer
+    pub rules: Vec<TextRule>,
+    /// Where the links in the buffer go, indexed by
+    /// the link metadata of their glyphs minus one
+    pub links: Vec<Arc<str>>,
+}
+
+impl ResolvedBuffer {
+    /// The areas the links in the buffer cover, one for each
+    /// line of a link, relative to the top left corner of the
+    /// buffer
+    pub fn link_rects(&self) -> Vec<(Rect, &Arc<str>)> {
+        let mut rects: Vec<(Rect, &Arc<str>)> = Vec::new();
+        if self.links.is_empty() {
+            return rects;
+        }
+        let buffer = self.buffer.read();
+        for run in buffer.layout_runs() {
+            let mut last_link = None;
+            for glyph in run.glyphs {
+                let Some(index) = (glyph.metadata & LINK_METADATA)
+                    .checked_sub(1)
+                    .filter(|index| *index < self.links.len())
+                else {
+                    last_link = None;
+                    continue;
+                };
+                let rect = Rect::from_min_size(
+                    Pos2::new(glyph.x, run.line_top),
+                    Vec2::new(glyph.w, run.line_height),
+                );
+                match rects.last_mut() {
+                    Some((last_rect, _)) if last_link == Some(index) => {
+                        *last_rect = last_rect.union(rect)
+                    }
+                    _ => rects.push((rect, &self.links[index])),
+                }
+                last_link = Some(index);
+            }
+        }
+        rects
+    }
+}
+
+/// A line drawn with text, like the lines between
+/// the rows of a table or the bar of a fraction
+#[derive(Clone, Copy)]
+pub struct TextRule {
+    pub points: [Pos2; 2],
+    pub width: f32,
+    pub color: Color32,
+}
+
+/// A formula in a paragraph, with its top left
+/// corner relative to the top left of the paragraph
+pub struct InlineFormula {
+    pub layout: MathLayout,
+    pub pos: Vec2,
+    pub color: Color32,
+}
+
+/// A subscript or superscript in a paragraph, with its
+/// top left corner relative to the top left of the paragraph
+pub struct InlineScript {
+    pub buffer: Buffer,
+    pub rect: Rect,
+}
+
+/// What a paragraph holds besides its text
+#[derive(Default)]
+pub struct InlineContent {
+    pub formulas: Vec<InlineFormula>,
+    pub scripts: Vec<InlineScript>,
+    /// Where the links go, indexed by the link
+    /// metadata of their glyphs minus one
+    pub links: Vec<Arc<str>>,
+    /// Underlines and strikethroughs
+    pub rules: Vec<TextRule>,
+}
+
+/// Where a buffer goes relative to the buffer before it
+#[derive(Default, Clone, Copy)]
+pub enum Stacking {
+    /// Under it, after a margin
+    #[default]
+    Margin,
+    /// A line of a code block, right under the line before
+    /// it unless it's the first line. The block spanning
+    /// these x coordinates is aligned as one, so its lines
+    /// keep their indentation and their line numbers
+    Code { first: bool, block: [f32; 2] },
+    /// Under it, starting a row of a table, with a
+    /// line between the two rows spanning these x
+    /// coordinates if the table has rules
+    TableRow { rule: Option<([f32; 2], Color32)> },
+    /// In the same row of a table
+    SameRow,
+    /// Part of a formula or a script in the last
+    /// buffer that wasn't inline, with its rect
+    /// relative to the top left corner of that buffer
+    Inline,
 }
 
 pub fn resolve_text_job(
@@ -96,6 +242,8 @@ pub fn resolve_text_job(
     let mut translation = Vec2::ZERO;
     let mut last_buffer_marker = false;
     let mut last_buffer_size = Rect::ZERO;
+    let mut last_host_min = Pos2::ZERO;
+    let mut rules = Vec::new();
 
     // Second pass:
     //
@@ -115,28 +263,87 @@ pub fn resolve_text_job(
             }
             buffer.metrics().font_size
         };
+        if let Stacking::Inline = buffer.stacking {
+            buffer.buffer_rect = buffer.buffer_rect.translate(last_host_min.to_vec2());
+            continue;
+        }
         // We use buffer.min.y to store whether the buffer that came before
         // it was a list number or not
-        if last_buffer_marker {
-            translation.y += buffer.buffer_rect.height() - last_buffer_size.height();
-        } else {
-            translation.y += last_margin + buffer.buffer_rect.height();
+        match buffer.stacking {
+            // Every cell of a row is as tall as the row
+            Stacking::SameRow => {}
+            _ if last_buffer_marker => {
+                translation.y += buffer.buffer_rect.height() - last_buffer_size.height();
+            }
+            Stacking::Code { first: false, .. } => translation.y += buffer.buffer_rect.height(),
+            Stacking::Inline => unreachable!(),
+            Stacking::Margin | Stacking::TableRow { .. } | Stacking::Code { first: true, .. } => {
+                translation.y += last_margin + buffer.buffer_rect.height();
+            }
         }
         last_buffer_marker = buffer.marker;
         last_buffer_size = buffer.buffer_rect;
-        buffer.buffer_rect = Align2([align_egui, egui::Align::BOTTOM]).align_size_within_rect(
-            buffer.buffer_rect.size(),
-            Rect::from_min_size(
-                Pos2::new(buffer.buffer_rect.min.x, 0.0),
-                Vec2::new(size_x - buffer.buffer_rect.min.x, translation.y),
+        buffer.buffer_rect = match buffer.stacking {
+            // Cells stay in their columns
+            Stacking::TableRow { .. } | Stacking::SameRow => Rect::from_min_size(
+                Pos2::new(
+                    buffer.buffer_rect.min.x,
+                    translation.y - buffer.buffer_rect.height(),
+                ),
+                buffer.buffer_rect.size(),
             ),
-        );
+            Stacking::Code {
+                block: [min_x, max_x],
+                ..
+            } => {
+                let block = Align2([align_egui, egui::Align::BOTTOM]).align_size_within_rect(
+                    Vec2::new(max_x - min_x, buffer.buffer_rect.height()),
+                    Rect::from_min_size(
+                        Pos2::new(min_x, 0.0),
+                        Vec2::new(size_x - min_x, translation.y),
+                    ),
+                );
+                Rect::from_min_size(
+                    Pos2::new(block.min.x + buffer.buffer_rect.min.x - min_x, block.min.y),
+                    buffer.buffer_rect.size(),
+                )
+            }
+            Stacking::Margin | Stacking::Inline => Align2([align_egui, egui::Align::BOTTOM])
+                .align_size_within_rect(
+                    buffer.buffer_rect.size(),
+                    Rect::from_min_size(
+                        Pos2::new(buffer.buffer_rect.min.x, 0.0),
+                        Vec2::new(size_x - buffer.buffer_rect.min.x, translation.y),
+                    ),
+                ),
+        };
+        if let Stacking::TableRow {
+            rule: Some(([min_x, max_x], color)),
+        } = buffer.stacking
+        {
+            let y = buffer.buffer_rect.min.y - last_margin / 2.0;
+            rules.push(TextRule {
+                points: [Pos2::new(min_x, y), Pos2::new(max_x, y)],
+                width: RULE_WIDTH,
+                color,
+            });
+        }
+        last_host_min = buffer.buffer_rect.min;
+        rules.extend(buffer.rules.iter().map(|rule| TextRule {
+            points: rule.points.map(|point| point + last_host_min.to_vec2()),
+            ..*rule
+        }));
         last_margin = font_size * MARGIN_MULTIPLIER;
     }
 
     (
         Vec2::new(size_x, translation.y),
-        ResolvedObjInner::Text { job, tags, fonts },
+        ResolvedObjInner::Text {
+            job,
+            tags,
+            fonts,
+            rules,
+        },
     )
 }
 
@@ -157,7 +364,7 @@ pub fn resolve_text_job_first_pass(
     for section in sections {
         match section {
             TextSection::Paragraph(p) => {
-                let (paragraph, buffer_size) = resolve_text_paragraph(
+                let (paragraph, buffer_size, inline) = resolve_text_paragraph(
                     p,
                     line_height,
                     alignment,
@@ -175,11 +382,15 @@ pub fn resolve_text_job_first_pass(
                     Some(TextTag::Code) => tags.push(ResolvedTextTag::Code(job.len())),
                     None => tags.push(ResolvedTextTag::Untagged(job.len())),
                 }
-                job.push(ResolvedBuffer {
-                    buffer: Arc::new(RwLock::new(paragraph)),
-                    buffer_rect: buffer_size.translate(translation),
-                    marker: false,
-                });
+                push_buffer(
+                    job,
+                    tags,
+                    paragraph,
+                    buffer_size.translate(translation),
+                    false,
+                    Stacking::Margin,
+                    inline,
+                );
             }
             TextSection::Blockquote(bq) => {
                 tags.push(ResolvedTextTag::BlockquoteStart);
@@ -200,7 +411,7 @@ pub fn resolve_text_job_first_pass(
                 tags.push(ResolvedTextTag::ListStart);
                 for (list_number, list_item) in list {
                     tags.push(ResolvedTextTag::ListItemStart);
-                    let (paragraph, buffer_size) = resolve_text_paragraph(
+                    let (paragraph, buffer_size, inline) = resolve_text_paragraph(
                         list_number,
                         line_height,
                         alignment,
@@ -214,11 +425,15 @@ pub fn resolve_text_job_first_pass(
                         Some(t) => tracing::warn!("Wacky tag `{:?}` on list label", t),
                         None => tags.push(ResolvedTextTag::Untagged(job.len())),
                     }
-                    job.push(ResolvedBuffer {
-                        buffer: Arc::new(RwLock::new(paragraph)),
-                        buffer_rect: buffer_size.translate(translation),
-                        marker: true,
-                    });
+                    push_buffer(
+                        job,
+                        tags,
+                        paragraph,
+                        buffer_size.translate(translation),
+                        true,
+                        Stacking::Margin,
+                        inline,
+                    );
 
                     tags.push(ResolvedTextTag::ListBodyStart);
                     size_x = size_x.max(resolve_text_job_first_pass(
@@ -237,6 +452,111 @@ pub fn resolve_text_job_first_pass(
                 }
                 tags.push(ResolvedTextTag::ListEnd);
             }
+            TextSection::Table(table) => {
+                size_x = size_x.max(resolve_table(
+                    table,
+                    job,
+                    tags,
+                    font_system,
+                    line_height,
+                    translation,
+                    max_width,
+                ));
+            }
+            TextSection::Code(lines) => {
+                let gutters: SmallVec<[_; 16]> = lines
+                    .iter()
+                    .map(|(gutter, _)| {
+                        gutter.as_ref().map(|gutter| {
+                            resolve_text_paragraph(
+                                gutter,
+                                line_height,
+                                Align::Left,
+                                font_system,
+                                max_width - translation.x,
+                            )
+                        })
+                    })
+                    .collect();
+                // Line numbers are right aligned against the code
+                let gutter_width = gutters
+                    .iter()
+                    .flatten()
+                    .map(|(_, size, _)| size.width())
+                    .fold(0.0, f32::max);
+                let code_margin = if gutter_width > 0.0 {
+                    lines
+                        .first()
+                        .map(|(_, line)| line.font_size * MARGIN_MULTIPLIER)
+                        .unwrap_or_default()
+                } else {
+                    0.0
+                };
+
+                let line_translation = translation + Vec2::new(gutter_width + code_margin, 0.0);
+                let resolved_lines: SmallVec<[_; 16]> = lines
+                    .iter()
+                    .map(|(_, line)| {
+                        resolve_text_paragraph(
+                            line,
+                            line_height,
+                            Align::Left,
+                            font_system,
+                            max_width - line_translation.x,
+                        )
+                    })
+                    .collect();
+                let block_max_x = resolved_lines
+                    .iter()
+                    .map(|(_, size, _)| size.width() + line_translation.x)
+                    .fold(translation.x, f32::max);
+                size_x = size_x.max(block_max_x);
+
+                for (index, (((gutter, line), resolved_gutter), resolved_line)) in
+                    lines.iter().zip(gutters).zip(resolved_lines).enumerate()
+                {
+                    let stacking = Stacking::Code {
+                        first: index == 0,
+                        block: [translation.x, block_max_x],
+                    };
+                    if let (Some(gutter), Some((paragraph, buffer_size, inline))) =
+                        (gutter, resolved_gutter)
+                    {
+                        match gutter.tag {
+                            Some(TextTag::Label) => tags.push(ResolvedTextTag::Label(job.len())),
+                            Some(t) => tracing::warn!("Wacky tag `{:?}` on line number", t),
+                            None => tags.push(ResolvedTextTag::Untagged(job.len())),
+                        }
+                        push_buffer(
+                            job,
+                            tags,
+                            paragraph,
+                            buffer_size.translate(
+                                translation + Vec2::new(gutter_width - buffer_size.width(), 0.0),
+                            ),
+                            true,
+                            stacking,
+                            inline,
+                        );
+                    }
+
+                    let (paragraph, buffer_size, inline) = resolved_line;
+                    match line.tag {
+                        Some(TextTag::Code) => tags.push(ResolvedTextTag::Code(job.len())),
+                        Some(t) => tracing::warn!("Wacky tag `{:?}` on line of code", t),
+                        None => tags.push(ResolvedTextTag::Untagged(job.len())),
+                    }
+                    push_buffer(
+                        job,
+                        tags,
+                        paragraph,
+                        buffer_size.translate(line_translation),
+                        false,
+                        stacking,
+                        inline,
+                    );
+                }
+            }
         }
     }
     if translation.x == 0.0 {
@@ -245,74 +565,276 @@ pub fn resolve_text_job_first_pass(
     size_x
 }
 
+/// Lays a table out as a grid, where each column is as wide as
+/// its widest cell, and gives the width of the object with it
+fn resolve_table(
+    table: &TextTable,
+    job: &mut SmallVec<[ResolvedBuffer; 1]>,
+    tags: &mut SmallVec<[ResolvedTextTag; 3]>,
+    font_system: &mut FontSystem,
+    line_height: Option<f32>,
+    translation: Vec2,
+    max_width: f32,
+) -> f32 {
+    let columns = table
+        .rows
+        .iter()
+        .map(|row| row.cells.len())
+        .max()
+        .unwrap_or_default();
+    let column_alignment = |column: usize| match table.alignments.get(column) {
+        Some(grezi_parser::text::Align::Center) => Align::Center,
+        Some(grezi_parser::text::Align::Right | grezi_parser::text::Align::End) => Align::Right,
+        _ => Align::Left,
+    };
+    let padding = table
+        .rows
+        .iter()
+        .find_map(|row| row.cells.first())
+        .map(|cell| cell.font_size * MARGIN_MULTIPLIER)
+        .unwrap_or_default();
+    let spacing = padding * columns.saturating_sub(1) as f32;
+    let cell_max_width = ((max_width - translation.x - spacing) / columns.max(1) as f32).max(0.0);
+
+    let mut rows: Vec<SmallVec<[(Buffer, Rect, InlineContent); 4]>> =
+        Vec::with_capacity(table.rows.len());
+    let mut widths = vec![0.0f32; columns];
+    for row in &table.rows {
+        rows.push(
+            row.cells
+                .iter()
+                .enumerate()
+                .map(|(column, cell)| {
+                    let (buffer, buffer_size, inline) = resolve_text_paragraph(
+                        cell,
+                        line_height,
+                        column_alignment(column),
+                        font_system,
+                        cell_max_width,
+                    );
+                    widths[column] = widths[column].max(buffer_size.width());
+                    (buffer, buffer_size, inline)
+                })
+                .collect(),
+        );
+    }
+    let table_width = widths.iter().sum::<f32>() + spacing;
+
+    tags.push(ResolvedTextTag::TableStart);
+    for (row_index, (row, cells)) in table.rows.iter().zip(rows).enumerate() {
+        tags.push(ResolvedTextTag::TableRowStart);
+        let row_height = cells
+            .iter()
+            .map(|(_, buffer_size, _)| buffer_size.height())
+            .fold(0.0, f32::max);
+        let mut x = translation.x;
+        for (column, ((cell, (buffer, buffer_size, inline)), width)) in
+            row.cells.iter().zip(cells).zip(&widths).enumerate()
+        {
+            let offset = match column_alignment(column) {
+                Align::Center => (width - buffer_size.width()) / 2.0,
+                Align::Right => width - buffer_size.width(),
+                _ => 0.0,
+            };
+            match (cell.tag, row.head) {
+                (Some(_), true) => tags.push(ResolvedTextTag::TableHeader(job.len())),
+                (Some(_), false) => tags.push(ResolvedTextTag::TableData(job.len())),
+                (None, _) => tags.push(ResolvedTextTag::Untagged(job.len())),
+            }
+            push_buffer(
+                job,
+                tags,
+                buffer,
+                Rect::from_min_size(
+                    Pos2::new(x + offset, translation.y),
+                    Vec2::new(buffer_size.width(), row_height),
+                ),
+                false,
+                if column == 0 {
+                    Stacking::TableRow {
+                        rule: table
+                            .rules
+                            .filter(|_| row_index != 0)
+                            .map(|color| ([translation.x, translation.x + table_width], color)),
+                    }
+                } else {
+                    Stacking::SameRow
+                },
+                inline,
+            );
+            x += width + padding;
+        }
+        tags.push(ResolvedTextTag::TableRowEnd);
+    }
+    tags.push(ResolvedTextTag::TableEnd);
+
+    table_width + translation.x
+}
+
+/// Adds a buffer to the job, followed by the text
+/// of the formulas and scripts inside of it
+fn push_buffer(
+    job: &mut SmallVec<[ResolvedBuffer; 1]>,
+    tags: &mut SmallVec<[ResolvedTextTag; 3]>,
+    buffer: Buffer,
+    buffer_rect: Rect,
+    marker: bool,
+    stacking: Stacking,
+    inline: InlineContent,
+) {
+    let host = job.len();
+    job.push(ResolvedBuffer {
+        buffer: Arc::new(RwLock::new(buffer)),
+        buffer_rect,
+        marker,
+        stacking,
+        rules: inline.rules,
+        links: inline.links,
+    });
+    for formula in inline.formulas {
+        tags.push(ResolvedTextTag::FormulaStart);
+        for run in formula.layout.runs {
+            let (width, height) = run.buffer.size();
+            tags.push(ResolvedTextTag::MathRun(job.len()));
+            job.push(ResolvedBuffer {
+                buffer: Arc::new(RwLock::new(run.buffer)),
+                buffer_rect: Rect::from_min_size(
+                    (formula.pos + Vec2::from(run.pos)).to_pos2(),
+                    Vec2::new(width.unwrap_or_default(), height.unwrap_or_default()),
+                ),
+                marker: true,
+                stacking: Stacking::Inline,
+                rules: Vec::new(),
+                links: Vec::new(),
+            });
+        }
+        tags.push(ResolvedTextTag::FormulaEnd);
+        job[host]
+            .rules
+            .extend(formula.layout.rules.iter().map(|rule| TextRule {
+                points:
+                    [rule.from, rule.to].map(|point| (formula.pos + Vec2::from(point)).to_pos2()),
+                width: rule.thickness,
+                color: formula.color,
+            }));
+    }
+    for script in inline.scripts {
+        tags.push(ResolvedTextTag::Script(job.len()));
+        job.push(ResolvedBuffer {
+            buffer: Arc::new(RwLock::new(script.buffer)),
+            buffer_rect: script.rect,
+            marker: true,
+            stacking: Stacking::Inline,
+            rules: Vec::new(),
+            links: Vec::new(),
+        });
+    }
+}
+
 pub fn resolve_text_paragraph(
     paragraph: &TextParagraph,
     line_height: Option<f32>,
     alignment: Align,
     font_system: &mut FontSystem,
     max_width: f32,
-) -> (Buffer, Rect) {
-    let mut buffer = Buffer::new(
-        font_system,
-        Metrics::new(
-            paragraph.font_size,
-            line_height.unwrap_or(paragraph.font_size * LINE_HEIGHT_MULTIPLIER),
-        ),
-    );
+) -> (Buffer, Rect, InlineContent) {
+    let line_height = line_height.unwrap_or(paragraph.font_size * LINE_HEIGHT_MULTIPLIER);
+    let mut buffer = Buffer::new(font_system, Metrics::new(paragraph.font_size, line_height));
+
+    // Formulas are laid out before the paragraph, so that
+    // the spans holding their place can be made as wide
+    // and as tall as them
+    let mut layouts: Vec<(MathLayout, Color32)> = Vec::with_capacity(paragraph.math.len());
+    let mut links: Vec<Arc<str>> = Vec::new();
+    // Scripts are laid out on their own too, since text
+    // can't be moved off the baseline in a buffer
+    let mut scripts: Vec<(Buffer, Rect, Baseline)> = Vec::new();
+    let mut spans: SmallVec<[(&str, Attrs); 4]> =
+        SmallVec::with_capacity(paragraph.rich_text.len());
+    for (rich_span, attrs) in &paragraph.rich_text {
+        let mut span_attrs = cosmic_attrs(attrs);
+        if let Some(math) = paragraph
+            .math
+            .get(layouts.len())
+            .filter(|_| rich_span.as_str() == MATH_PLACEHOLDER)
+        {
+            let layout = match MathMLDoc::new(&math.mathml) {
+                Ok(doc) => doc.resolve(font_system, &span_attrs, paragraph.font_size, math.display),
+                Err(e) => {
+                    tracing::warn!("Invalid MathML in paragraph: {}", e);
+                    MathLayout::default()
+                }
+            };
+            span_attrs.metadata = MATH_METADATA | (layouts.len() << INLINE_INDEX_SHIFT);
+            span_attrs.letter_spacing_opt = Some(LetterSpacing(layout.width / paragraph.font_size));
+            // The baseline sits around the middle of the line
+            span_attrs.metrics_opt = Some(
+                Metrics::new(
+                    paragraph.font_size,
+                    line_height.max(layout.ascent.max(layout.descent) * 2.0),
+                )
+                .into(),
+            );
+            layouts.push((layout, attrs.color));
+            spans.push(("\u{200B}", span_attrs));
+        } else {
+            if let Some(link) = &attrs.link {
+                span_attrs.metadata = match links.iter().position(|l| l == link) {
+                    Some(index) => index + 1,
+                    None => {
+                        links.push(Arc::clone(link));
+                        links.len()
+                    }
+                };
+            }
+            if attrs.underline {
+                span_attrs.metadata |= UNDERLINE_METADATA;
+            }
+            if attrs.strikethrough {
+                span_attrs.metadata |= STRIKETHROUGH_METADATA;
+            }
+            if attrs.baseline == Baseline::Normal {
+                spans.push((rich_span.as_str(), span_attrs));
+            } else {
+                let font_size = attrs.font_size.unwrap_or(paragraph.font_size);
+                let script_size = font_size * SCRIPT_SCALE;
+                let mut script = Buffer::new(
+                    font_system,
+                    Metrics::new(script_size, script_size * LINE_HEIGHT_MULTIPLIER),
+                );
+                // The script is sized by its buffer instead
+                let mut script_attrs = cosmic_attrs(attrs);
+                script_attrs.metrics_opt = None;
+                script.set_rich_text(
+                    font_system,
+                    [(rich_span.as_str(), script_attrs)],
+                    &Attrs::new(),
+                    egui_glyphon::cosmic_text::Shaping::Advanced,
+                    None,
+                );
+                script.shape_until_scroll(font_system, false);
+                let mut script_rect = measure_buffer(&script);
+                script_rect.max += Vec2::splat(5.0);
+                script.set_size(
+                    font_system,
+                    Some(script_rect.width()),
+                    Some(script_rect.height()),
+                );
+                script.shape_until_scroll(font_system, true);
+
+                span_attrs.metadata |= SCRIPT_METADATA | (scripts.len() << INLINE_INDEX_SHIFT);
+                span_attrs.letter_spacing_opt =
+                    Some(LetterSpacing((script_rect.width() - 5.0) / font_size));
+                scripts.push((script, script_rect, attrs.baseline));
+                spans.push(("\u{200B}", span_attrs));
+            }
+        }
+    }
 
     buffer.set_size(font_system, Some(max_width), None);
     buffer.set_rich_text(
         font_system,
-        paragraph.rich_text.iter().map(|(rich_span, attrs)| {
-            (
-                rich_span.as_str(),
-                Attrs {
-                    color_opt: {
-                        let color = attrs.color.to_srgba_unmultiplied();
-                        Some(Color::rgba(color[0], color[1], color[2], color[3]))
-                    },
-                    family: match &attrs.family {
-                        grezi_parser::text::Family::Name(n) => Family::Name(n.deref()),
-                        grezi_parser::text::Family::Serif => Family::Serif,
-                        grezi_parser::text::Family::SansSerif => Family::SansSerif,
-                        grezi_parser::text::Family::Cursive => Family::Cursive,
-                        grezi_parser::text::Family::Fantasy => Family::Fantasy,
-                        grezi_parser::text::Family::Monospace => Family::Monospace,
-                    },
-                    stretch: match attrs.stretch {
-                        grezi_parser::text::Stretch::UltraCondensed => Stretch::UltraCondensed,
-                        grezi_parser::text::Stretch::ExtraCondensed => Stretch::ExtraCondensed,
-                        grezi_parser::text::Stretch::Condensed => Stretch::Condensed,
-                        grezi_parser::text::Stretch::SemiCondensed => Stretch::SemiCondensed,
-                        grezi_parser::text::Stretch::Normal => Stretch::Normal,
-                        grezi_parser::text::Stretch::SemiExpanded => Stretch::SemiExpanded,
-                        grezi_parser::text::Stretch::Expanded => Stretch::Expanded,
-                        grezi_parser::text::Stretch::ExtraExpanded => Stretch::ExtraExpanded,
-                        grezi_parser::text::Stretch::UltraExpanded => Stretch::UltraExpanded,
-                    },
-                    style: match attrs.style {
-                        grezi_parser::text::Style::Normal => Style::Normal,
-                        grezi_parser::text::Style::Italic => Style::Italic,
-                        grezi_parser::text::Style::Oblique => Style::Oblique,
-                    },
-                    // TODO: Use for links
-                    metadata: 0,
-                    weight: Weight(attrs.weight.0),
-                    cache_key_flags: CacheKeyFlags::empty(),
-                    metrics_opt: None,
-                    letter_spacing_opt: None,
-                    font_features: {
-                        let mut features = FontFeatures::new();
-                        features
-                            .enable(FeatureTag::STANDARD_LIGATURES)
-                            .enable(FeatureTag::CONTEXTUAL_LIGATURES)
-                            .enable(FeatureTag::CONTEXTUAL_ALTERNATES)
-                            .enable(FeatureTag::DISCRETIONARY_LIGATURES);
-                        features
-                    },
-                },
-            )
-        }),
+        spans,
         &Attrs::new(),
         egui_glyphon::cosmic_text::Shaping::Advanced,
         Some(alignment),
@@ -330,7 +852,164 @@ pub fn resolve_text_paragraph(
 
     buffer.shape_until_scroll(font_system, true);
 
-    (buffer, buffer_size)
+    let mut positions = vec![None; layouts.len()];
+    let mut script_positions = vec![None; scripts.len()];
+    for run in buffer.layout_runs() {
+        for glyph in run.glyphs {
+            let index = (glyph.metadata & INLINE_INDEX_METADATA) >> INLINE_INDEX_SHIFT;
+            if glyph.metadata & MATH_METADATA != 0 {
+                if let Some(((layout, _), position)) =
+                    layouts.get(index).zip(positions.get_mut(index))
+                {
+                    *position = Some(Vec2::new(glyph.x, run.line_y - layout.ascent));
+                }
+            } else if glyph.metadata & SCRIPT_METADATA != 0 {
+                if let Some(((script, _, baseline), position)) =
+                    scripts.get(index).zip(script_positions.get_mut(index))
+                {
+                    let shift = match baseline {
+                        Baseline::Subscript => glyph.font_size * SUBSCRIPT_SHIFT,
+                        _ => -glyph.font_size * SUPERSCRIPT_SHIFT,
+                    };
+                    let script_baseline = script
+                        .layout_runs()
+                        .next()
+                        .map(|run| run.line_y)
+                        .unwrap_or_default();
+                    *position = Some(Vec2::new(glyph.x, run.line_y + shift - script_baseline));
+                }
+            }
+        }
+    }
+    let formulas = layouts
+        .into_iter()
+        .zip(positions)
+        .filter_map(|((layout, color), pos)| {
+            Some(InlineFormula {
+                layout,
+                pos: pos?,
+                color,
+            })
+        })
+        .collect();
+
+    let scripts = scripts
+        .into_iter()
+        .zip(script_positions)
+        .filter_map(|((buffer, rect, _), pos)| {
+            Some(InlineScript {
+                buffer,
+                rect: rect.translate(pos?),
+            })
+        })
+        .collect();
+    let rules = decoration_rules(&buffer);
+
+    (
+        buffer,
+        buffer_size,
+        InlineContent {
+            formulas,
+            scripts,
+            links,
+            rules,
+        },
+    )
+}
+
+/// Lines under and through the glyphs of a buffer that
+/// are underlined or struck through, relative to the top
+/// left corner of the buffer
+fn decoration_rules(buffer: &Buffer) -> Vec<TextRule> {
+    let mut rules: Vec<TextRule> = Vec::new();
+    for run in buffer.layout_runs() {
+        // Where the lines under the last glyph are in `rules`,
+        // so that the lines of the next glyph can continue them
+        let mut last = [None; 2];
+        for glyph in run.glyphs {
+            let color = glyph
+                .color_opt
+                .map(|color| {
+                    Color32::from_rgba_unmultiplied(color.r(), color.g(), color.b(), color.a())
+                })
+                .unwrap_or(Color32::WHITE);
+            for (last, (flag, offset)) in last.iter_mut().zip([
+                (UNDERLINE_METADATA, UNDERLINE_OFFSET),
+                (STRIKETHROUGH_METADATA, STRIKETHROUGH_OFFSET),
+            ]) {
+                if glyph.metadata & flag == 0 {
+                    *last = None;
+                    continue;
+                }
+                let y = run.line_y + glyph.font_size * offset;
+                match last.map(|index: usize| &mut rules[index]) {
+                    Some(rule) if rule.color == color && rule.points[0].y == y => {
+                        rule.points[1].x = glyph.x + glyph.w;
+                    }
+                    _ => {
+                        *last = Some(rules.len());
+                        rules.push(TextRule {
+                            points: [Pos2::new(glyph.x, y), Pos2::new(glyph.x + glyph.w, y)],
+                            width: glyph.font_size * DECORATION_WIDTH,
+                            color,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    rules
+}
+
+/// Converts the attributes of a span to the
+/// ones cosmic-text lays text out with
+fn cosmic_attrs(attrs: &grezi_parser::text::Attrs) -> Attrs<'_> {
+    Attrs {
+        color_opt: {
+            let color = attrs.color.to_srgba_unmultiplied();
+            Some(Color::rgba(color[0], color[1], color[2], color[3]))
+        },
+        family: match &attrs.family {
+            grezi_parser::text::Family::Name(n) => Family::Name(n.deref()),
+            grezi_parser::text::Family::Serif => Family::Serif,
+            grezi_parser::text::Family::SansSerif => Family::SansSerif,
+            grezi_parser::text::Family::Cursive => Family::Cursive,
+            grezi_parser::text::Family::Fantasy => Family::Fantasy,
+            grezi_parser::text::Family::Monospace => Family::Monospace,
+        },
+        stretch: match attrs.stretch {
+            grezi_parser::text::Stretch::UltraCondensed => Stretch::UltraCondensed,
+            grezi_parser::text::Stretch::ExtraCondensed => Stretch::ExtraCondensed,
+            grezi_parser::text::Stretch::Condensed => Stretch::Condensed,
+            grezi_parser::text::Stretch::SemiCondensed => Stretch::SemiCondensed,
+            grezi_parser::text::Stretch::Normal => Stretch::Normal,
+            grezi_parser::text::Stretch::SemiExpanded => Stretch::SemiExpanded,
+            grezi_parser::text::Stretch::Expanded => Stretch::Expanded,
+            grezi_parser::text::Stretch::ExtraExpanded => Stretch::ExtraExpanded,
+            grezi_parser::text::Stretch::UltraExpanded => Stretch::UltraExpanded,
+        },
+        style: match attrs.style {
+            grezi_parser::text::Style::Normal => Style::Normal,
+            grezi_parser::text::Style::Italic => Style::Italic,
+            grezi_parser::text::Style::Oblique => Style::Oblique,
+        },
+        metadata: 0,
+        weight: Weight(attrs.weight.0),
+        cache_key_flags: CacheKeyFlags::empty(),
+        metrics_opt: attrs
+            .font_size
+            .map(|size| Metrics::new(size, size * LINE_HEIGHT_MULTIPLIER).into()),
+        letter_spacing_opt: None,
+        font_features: {
+            let mut features = FontFeatures::new();
+            features
+                .enable(FeatureTag::STANDARD_LIGATURES)
+                .enable(FeatureTag::CONTEXTUAL_LIGATURES)
+                .enable(FeatureTag::CONTEXTUAL_ALTERNATES)
+                .enable(FeatureTag::DISCRETIONARY_LIGATURES);
+            features
+        },
+    }
 }
 
 pub fn selection_rects(
@@ -20,7 +20,7 @@ use egui_glyphon::{
 use eyre::{Context, ContextCompat, OptionExt, bail};
 use grezi_egui::{GrzResolvedSlide, ResolvedTextTag};
 use grezi_font_serde::{FontRef, IndexSliceSerializer};
-use grezi_parser::{GrzRoot, HIGHLIGHT_COLOR_DEFAULT};
+use grezi_parser::GrzRoot;
 use image::ImageFormat;
 use indexmap::IndexSet;
 use keyframe::functions::EaseOutCubic;
@@ -144,7 +144,7 @@ impl<'a> GrzExporter<'a> {
 
         let mut textures = HashMap::new();
 
-        for index in index_range {
+        for index in index_range.clone() {
             let mut text_buffers = Vec::new();
             let resolved_slide = grezi_egui::GrzResolvedSlide::resolve_slide(
                 self.file,
@@ -182,8 +182,41 @@ impl<'a> GrzExporter<'a> {
                 &mut self.used_faces,
                 text_buffers,
                 tags,
+                &resolved_slide.highlight_rects(Rect::from_min_size(Pos2::ZERO, size)),
             );
 
+            // Links cover the text they're on, so slides can point
+            // to the pages other slides were exported to
+            for (rect, link) in resolved_slide.link_rects(Rect::from_min_size(Pos2::ZERO, size)) {
+                let target = if link.starts_with('#') {
+                    match self
+                        .file
+                        .slide_index(&link)
+                        .filter(|target| index_range.contains(target))
+                    {
+                        Some(target) => format!("page={}", target - index_range.start + 1),
+                        None => {
+                            tracing::warn!("Link to `{}` doesn't go to an exported slide", link);
+                            continue;
+                        }
+                    }
+                } else {
+                    format!("uri='{}'", link.replace('\\', "\\\\").replace('\'', "\\'"))
+                };
+                cairo_ctx.tag_begin(
+                    "Link",
+                    &format!(
+                        "rect=[{} {} {} {}] {}",
+                        rect.min.x,
+                        rect.min.y,
+                        rect.width(),
+                        rect.height(),
+                        target
+                    ),
+                );
+                cairo_ctx.tag_end("Link");
+            }
+
             cairo_ctx.show_page().unwrap();
 
             if !image_data.is_empty() {
@@ -247,6 +280,7 @@ pub fn fonts_to_ft(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn cairo_draw(
     output: egui::FullOutput,
     textures: &mut HashMap<TextureId, (ImageSurface, bool)>,
@@ -256,6 +290,7 @@ pub fn cairo_draw(
     fonts: &mut HashMap<ID, (freetype::Face, cairo::FontFace)>,
     buffers: Vec<BufferWithTextArea>,
     tags: Vec<ResolvedTextTag>,
+    highlights: &[Rect],
 ) {
     for (id, tex) in output.textures_delta.set {
         let surface = match tex.image {
@@ -316,6 +351,7 @@ pub fn cairo_draw(
             ft,
             Arc::clone(&font_system),
             fonts,
+            highlights,
         );
     }
 
@@ -336,12 +372,21 @@ pub fn cairo_draw_shape(
     ft: &freetype::Library,
     font_system: Arc<Mutex<FontSystem>>,
     fonts: &mut HashMap<ID, (freetype::Face, cairo::FontFace)>,
+    highlights: &[Rect],
 ) {
     match shape {
         egui::Shape::Noop | egui::Shape::Text(_) => {}
         egui::Shape::Vec(shapes) => {
             for shape in shapes {
-                cairo_draw_shape(ctx, shape, textures, ft, Arc::clone(&font_system), fonts);
+                cairo_draw_shape(
+                    ctx,
+                    shape,
+                    textures,
+                    ft,
+                    Arc::clone(&font_system),
+                    fonts,
+                    highlights,
+                );
             }
         }
         egui::Shape::LineSegment { points, stroke } => {
@@ -360,20 +405,12 @@ pub fn cairo_draw_shape(
             ctx.stroke().unwrap();
         }
         egui::Shape::Rect(rect) => {
-            /// If someone could explain why cairo's alpha is different
-            /// than egui's alpha, I'd love to know
-            pub const HIGHLIGHT_COLOR_CAIRO: Color32 = {
-                let color = Color32::LIGHT_YELLOW;
-                Color32::from_rgba_premultiplied(
-                    (color.r() as f32 * 0.5 + 0.5) as u8,
-                    (color.g() as f32 * 0.5 + 0.5) as u8,
-                    (color.b() as f32 * 0.5 + 0.5) as u8,
-                    (color.a() as f32 * 0.5 + 0.5) as u8,
-                )
-            };
-
-            let fill = if rect.fill == HIGHLIGHT_COLOR_DEFAULT {
-                HIGHLIGHT_COLOR_CAIRO
+            let is_highlight = highlights.iter().any(|highlight| {
+                (highlight.min - rect.rect.min).length() < 0.5
+                    && (highlight.max - rect.rect.max).length() < 0.5
+            });
+            let fill = if is_highlight {
+                cairo_highlight_color(rect.fill)
             } else {
                 rect.fill
             };
@@ -533,6 +570,24 @@ pub fn cairo_draw_shape(
     }
 }
 
+/// Highlights are usually brighter than they are opaque, which
+/// egui adds to what's under them, but cairo can't blend like
+/// that. Instead, they're drawn as the most transparent color
+/// that adds as much light
+fn cairo_highlight_color(color: Color32) -> Color32 {
+    let brightest = color.r().max(color.g()).max(color.b());
+    if brightest <= color.a() {
+        return color;
+    }
+    let scale = |c: u8| (c as u32 * 255 / brightest as u32) as u8;
+    Color32::from_rgba_premultiplied(
+        scale(color.r()),
+        scale(color.g()),
+        scale(color.b()),
+        brightest,
+    )
+}
+
 fn cairo_draw_text(
     ctx: &cairo::Context,
     ft: &freetype::Library,
@@ -583,6 +638,30 @@ fn cairo_draw_text(
                 ctx.tag_end("BlockQuote");
                 continue;
             }
+            ResolvedTextTag::TableStart => {
+                ctx.tag_begin("Table", "");
+                continue;
+            }
+            ResolvedTextTag::TableEnd => {
+                ctx.tag_end("Table");
+                continue;
+            }
+            ResolvedTextTag::TableRowStart => {
+                ctx.tag_begin("TR", "");
+                continue;
+            }
+            ResolvedTextTag::TableRowEnd => {
+                ctx.tag_end("TR");
+                continue;
+            }
+            ResolvedTextTag::FormulaStart => {
+                ctx.tag_begin("Formula", "");
+                continue;
+            }
+            ResolvedTextTag::FormulaEnd => {
+                ctx.tag_end("Formula");
+                continue;
+            }
             ResolvedTextTag::Paragraph(p) => {
                 ctx.tag_begin("P", "");
                 let buffer = buffers.get(p).unwrap();
@@ -608,6 +687,28 @@ fn cairo_draw_text(
                 cairo_draw_buffer(ctx, buffer, ft, Arc::clone(&font_system), fonts);
                 ctx.tag_end(&header_tag);
             }
+            ResolvedTextTag::TableHeader(th) => {
+                ctx.tag_begin("TH", "");
+                let buffer = buffers.get(th).unwrap();
+                cairo_draw_buffer(ctx, buffer, ft, Arc::clone(&font_system), fonts);
+                ctx.tag_end("TH");
+            }
+            ResolvedTextTag::TableData(td) => {
+                ctx.tag_begin("TD", "");
+                let buffer = buffers.get(td).unwrap();
+                cairo_draw_buffer(ctx, buffer, ft, Arc::clone(&font_system), fonts);
+                ctx.tag_end("TD");
+            }
+            ResolvedTextTag::MathRun(run) => {
+                let buffer = buffers.get(run).unwrap();
+                cairo_draw_buffer(ctx, buffer, ft, Arc::clone(&font_system), fonts);
+            }
+            ResolvedTextTag::Script(script) => {
+                ctx.tag_begin("Span", "");
+                let buffer = buffers.get(script).unwrap();
+                cairo_draw_buffer(ctx, buffer, ft, Arc::clone(&font_system), fonts);
+                ctx.tag_end("Span");
+            }
             ResolvedTextTag::Untagged(p) => {
                 ctx.tag_begin("Figure", "");
                 let buffer = buffers.get(p).unwrap();
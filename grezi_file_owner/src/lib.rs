@@ -48,6 +48,16 @@ impl DefaultOwner {
                     self.slide_index = self.slide_index.saturating_sub(1);
                     reset_time = false;
                 }
+                FileOwnerMessage::Link(link) => match self.root.slideshow.slide_index(&link) {
+                    Some(index) => {
+                        self.slide_index = index;
+                        reset_time = true;
+                    }
+                    None => {
+                        eprintln!("No slide is named `{}`", link);
+                        continue;
+                    }
+                },
                 FileOwnerMessage::ResetFile => {
                     let parse_result = self.root.update_file().unwrap();
                     if parse_result.has_errors() {
@@ -100,10 +110,16 @@ impl DefaultOwner {
 /// owner of the current `GrzFile`
 #[derive(Debug)]
 pub enum FileOwnerMessage {
-    Index { index: usize, reset_time: bool },
+    Index {
+        index: usize,
+        reset_time: bool,
+    },
     ResetFile,
     Next(bool),
     Previous,
+    /// Go to the slide a link like
+    /// `#intro` points to
+    Link(Arc<str>),
 }
 
 #[derive(Clone)]
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+
+use helix_lsp_types as lsp_types;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic, NumberOrString,
+    TextEdit, Url, WorkspaceEdit,
+};
+use ropey::Rope;
+use tree_sitter::{Node, Tree};
+use tree_sitter_grz::{FieldName, NodeKind};
+
+use super::formatter::{byte_range_from_char_range, char_range_from_byte_range, format_snippet};
+
+pub fn code_actions(
+    params: CodeActionParams,
+    current_rope: &Rope,
+    tree: &Option<Tree>,
+) -> Option<Vec<CodeActionOrCommand>> {
+    let tree = tree.as_ref()?;
+    let uri = params.text_document.uri;
+    let mut actions = Vec::new();
+
+    for diagnostic in params.context.diagnostics {
+        let Some(NumberOrString::String(code)) = &diagnostic.code else {
+            continue;
+        };
+        let Ok(range) = byte_range_from_char_range(diagnostic.range, current_rope) else {
+            continue;
+        };
+        let Some(node) = tree
+            .root_node()
+            .descendant_for_point_range(range.start_point, range.end_point)
+        else {
+            continue;
+        };
+        let name = current_rope.byte_slice(node.byte_range()).to_string();
+
+        match code.as_str() {
+            "grezi::unused_object" | "grezi::unused_viewbox" => {
+                let Some(definition) = top_level_node(node) else {
+                    continue;
+                };
+                let Some(edit) = remove_definition(definition, current_rope) else {
+                    continue;
+                };
+                actions.push(quick_fix(
+                    format!("Remove `{}`", name),
+                    &uri,
+                    vec![edit],
+                    &diagnostic,
+                ));
+            }
+            "grezi::object_not_on_slide" => {
+                let Some(function) = ancestor_of_kind(node, NodeKind::SymSlideFunction) else {
+                    continue;
+                };
+                if let Some(edit) = remove_function(function, current_rope) {
+                    let function_name = function
+                        .child_by_field_id(FieldName::FieldFunction as u16)
+                        .map(|f| current_rope.byte_slice(f.byte_range()).to_string())
+                        .unwrap_or_default();
+                    actions.push(quick_fix(
+                        format!("Remove `{}` action", function_name),
+                        &uri,
+                        vec![edit],
+                        &diagnostic,
+                    ));
+                }
+
+                if !is_declared(&name, tree, current_rope) {
+                    let Some(edit) = top_level_node(node)
+                        .and_then(|slide| declare_object(&name, slide, current_rope))
+                    else {
+                        continue;
+                    };
+                    actions.push(quick_fix(
+                        format!("Declare object `{}`", name),
+                        &uri,
+                        vec![edit],
+                        &diagnostic,
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Ok(range) = byte_range_from_char_range(params.range, current_rope) {
+        if let Some(node) = tree
+            .root_node()
+            .descendant_for_point_range(range.start_point, range.start_point)
+        {
+            refactors(node, tree, current_rope, &uri, &mut actions);
+        }
+    }
+
+    Some(actions)
+}
+
+/// Actions that aren't tied to a diagnostic, and
+/// only depend on where the cursor is
+fn refactors(
+    node: Node,
+    tree: &Tree,
+    current_rope: &Rope,
+    uri: &Url,
+    actions: &mut Vec<CodeActionOrCommand>,
+) {
+    if let Some(action) = ancestor_of_kind(node, NodeKind::SymSlideVb)
+        .and_then(|slide_vb| extract_viewbox(slide_vb, tree, current_rope, uri))
+    {
+        actions.push(action);
+    }
+
+    if let Some(action) = ancestor_of_kind(node, NodeKind::SymObjInner)
+        .and_then(|obj_inner| convert_text_type(obj_inner, current_rope, uri))
+    {
+        actions.push(action);
+    }
+
+    if let Some(slide) = top_level_node(node).filter(|n| {
+        matches!(
+            NodeKind::from(n.kind_id()),
+            NodeKind::SymSlide | NodeKind::SymActions
+        )
+    }) {
+        let slide_text = current_rope.byte_slice(slide.byte_range()).to_string();
+        actions.extend(insert_after(
+            "Duplicate slide",
+            slide,
+            slide_text,
+            current_rope,
+            uri,
+        ));
+        actions.extend(insert_after(
+            "Insert slide that keeps every object on screen",
+            slide,
+            "{ .., }[]".to_string(),
+            current_rope,
+            uri,
+        ));
+    }
+}
+
+/// Moves an inline viewbox (`Obj| ViewBox[0] ^ 1:2, 1:2, ][0]`)
+/// into a top level viewbox, and references it instead
+fn extract_viewbox(
+    slide_vb: Node,
+    tree: &Tree,
+    current_rope: &Rope,
+    uri: &Url,
+) -> Option<CodeActionOrCommand> {
+    if NodeKind::from(slide_vb.child(0)?.kind_id()) != NodeKind::AnonSymPIPE {
+        return None;
+    }
+    let mut cursor = slide_vb.walk();
+    let split_on = slide_vb
+        .named_children(&mut cursor)
+        .find(|n| NodeKind::from(n.kind_id()) == NodeKind::SymVbRef)?;
+    let body = slide_vb.child_by_field_id(FieldName::FieldBody as u16)?;
+    let index = slide_vb.child_by_field_id(FieldName::FieldViewboxIndex as u16)?;
+
+    let mut root_cursor = tree.walk();
+    let taken: Vec<String> = tree
+        .root_node()
+        .named_children(&mut root_cursor)
+        .filter(|n| NodeKind::from(n.kind_id()) == NodeKind::SymViewbox)
+        .filter_map(|n| n.child_by_field_id(FieldName::FieldName as u16))
+        .map(|n| current_rope.byte_slice(n.byte_range()).to_string())
+        .collect();
+    let name = std::iter::once("ExtractedViewBox".to_string())
+        .chain((2..).map(|n| format!("ExtractedViewBox{}", n)))
+        .find(|name| !taken.contains(name))?;
+
+    let text = |node: Node| current_rope.byte_slice(node.byte_range()).to_string();
+    let definition = format_snippet(format!("{}: {} {}", name, text(split_on), text(body)));
+
+    Some(refactor(
+        format!("Extract inline viewbox into `{}`", name),
+        CodeActionKind::REFACTOR_EXTRACT,
+        uri,
+        vec![
+            insert_before_edit(top_level_node(slide_vb)?, definition + "\n", current_rope)?,
+            TextEdit {
+                range: char_range_from_byte_range(slide_vb.range(), current_rope).ok()?,
+                new_text: format!(": {}{}", name, text(index)),
+            },
+        ],
+    ))
+}
+
+/// Turns a `Header` into a `Paragraph`, and vice versa
+fn convert_text_type(
+    obj_inner: Node,
+    current_rope: &Rope,
+    uri: &Url,
+) -> Option<CodeActionOrCommand> {
+    let ty = obj_inner.child_by_field_id(FieldName::FieldTy as u16)?;
+    let ty_text = current_rope.byte_slice(ty.byte_range());
+    let new_ty = if ty_text == "Header" {
+        "Paragraph"
+    } else if ty_text == "Paragraph" {
+        "Header"
+    } else {
+        return None;
+    };
+
+    Some(refactor(
+        format!("Convert to `{}`", new_ty),
+        CodeActionKind::REFACTOR_REWRITE,
+        uri,
+        vec![TextEdit {
+            range: char_range_from_byte_range(ty.range(), current_rope).ok()?,
+            new_text: new_ty.to_string(),
+        }],
+    ))
+}
+
+fn insert_after(
+    title: &str,
+    definition: Node,
+    text: String,
+    current_rope: &Rope,
+    uri: &Url,
+) -> Option<CodeActionOrCommand> {
+    let mut range = definition.range();
+    range.start_byte = range.end_byte;
+    range.start_point = range.end_point;
+
+    Some(refactor(
+        title.to_string(),
+        CodeActionKind::REFACTOR,
+        uri,
+        vec![TextEdit {
+            range: char_range_from_byte_range(range, current_rope).ok()?,
+            new_text: format!("\n\n{}", format_snippet(text).trim_end()),
+        }],
+    ))
+}
+
+fn refactor(
+    title: String,
+    kind: CodeActionKind,
+    uri: &Url,
+    edits: Vec<TextEdit>,
+) -> CodeActionOrCommand {
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title,
+        kind: Some(kind),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), edits)])),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+}
+
+fn quick_fix(
+    title: String,
+    uri: &Url,
+    edits: Vec<TextEdit>,
+    diagnostic: &Diagnostic,
+) -> CodeActionOrCommand {
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), edits)])),
+            ..Default::default()
+        }),
+        is_preferred: Some(true),
+        ..Default::default()
+    })
+}
+
+fn ancestor_of_kind(mut node: Node, kind: NodeKind) -> Option<Node> {
+    while NodeKind::from(node.kind_id()) != kind {
+        node = node.parent()?;
+    }
+    Some(node)
+}
+
+fn top_level_node(mut node: Node) -> Option<Node> {
+    while NodeKind::from(node.parent()?.kind_id()) != NodeKind::SymSourceFile {
+        node = node.parent()?;
+    }
+    Some(node)
+}
+
+fn is_declared(name: &str, tree: &Tree, current_rope: &Rope) -> bool {
+    let mut cursor = tree.walk();
+    tree.root_node()
+        .named_children(&mut cursor)
+        .filter(|n| NodeKind::from(n.kind_id()) == NodeKind::SymObj)
+        .filter_map(|n| n.child_by_field_id(FieldName::FieldName as u16))
+        .any(|n| current_rope.byte_slice(n.byte_range()) == name)
+}
+
+/// Removes a top level definition, along with
+/// the whitespace that follows it
+fn remove_definition(definition: Node, current_rope: &Rope) -> Option<TextEdit> {
+    let mut range = definition.range();
+    if let Some(whitespace) = definition
+        .next_sibling()
+        .filter(|n| NodeKind::from(n.kind_id()) == NodeKind::SymWhitespace)
+    {
+        range.end_byte = whitespace.end_byte();
+        range.end_point = whitespace.end_position();
+    }
+
+    Some(TextEdit {
+        range: char_range_from_byte_range(range, current_rope).ok()?,
+        new_text: String::new(),
+    })
+}
+
+/// Removes an action from a `slide_functions` block, along
+/// with the comma that separates it from its neighbor
+fn remove_function(function: Node, current_rope: &Rope) -> Option<TextEdit> {
+    let is_function = |n: &Node| NodeKind::from(n.kind_id()) == NodeKind::SymSlideFunction;
+    let mut range = function.range();
+
+    let mut next = function.next_named_sibling();
+    while let Some(node) = next.filter(|n| n.is_extra()) {
+        next = node.next_named_sibling();
+    }
+    let mut previous = function.prev_named_sibling();
+    while let Some(node) = previous.filter(|n| n.is_extra()) {
+        previous = node.prev_named_sibling();
+    }
+
+    if let Some(next) = next.filter(is_function) {
+        range.end_byte = next.start_byte();
+        range.end_point = next.start_position();
+    } else if let Some(previous) = previous.filter(is_function) {
+        range.start_byte = previous.end_byte();
+        range.start_point = previous.end_position();
+    }
+
+    Some(TextEdit {
+        range: char_range_from_byte_range(range, current_rope).ok()?,
+        new_text: String::new(),
+    })
+}
+
+/// Declares an empty `Paragraph` right before the slide
+/// that references it
+fn declare_object(name: &str, slide: Node, current_rope: &Rope) -> Option<TextEdit> {
+    insert_before_edit(
+        slide,
+        format_snippet(format!("{}: Paragraph(value: \"\")", name)) + "\n",
+        current_rope,
+    )
+}
+
+fn insert_before_edit(definition: Node, text: String, current_rope: &Rope) -> Option<TextEdit> {
+    let mut range = definition.range();
+    range.end_byte = range.start_byte;
+    range.end_point = range.start_point;
+
+    Some(TextEdit {
+        range: char_range_from_byte_range(range, current_rope).ok()?,
+        new_text: text,
+    })
+}
@@ -0,0 +1,61 @@
+use helix_lsp_types as lsp_types;
+use lsp_types::{CodeLens, Command, Url};
+use ropey::Rope;
+use tree_sitter::Tree;
+use tree_sitter_grz::NodeKind;
+
+use super::formatter::char_range_from_byte_range;
+
+/// Jumps the live preview to a slide. Takes the URI
+/// of the file and the index of the slide
+pub const PRESENT_COMMAND: &str = "grezi.presentFromHere";
+/// Exports a slide to a PNG next to the GRZ file. Takes
+/// the URI of the file and the index of the slide
+pub const EXPORT_COMMAND: &str = "grezi.exportSlide";
+
+pub fn code_lenses(
+    current_rope: &Rope,
+    currently_open: &Url,
+    tree: &Option<Tree>,
+) -> Option<Vec<CodeLens>> {
+    let tree = tree.as_ref()?;
+    let mut lenses = Vec::new();
+
+    let mut cursor = tree.walk();
+    for (index, slide) in tree
+        .root_node()
+        .named_children(&mut cursor)
+        .filter(|n| {
+            matches!(
+                NodeKind::from(n.kind_id()),
+                NodeKind::SymSlide | NodeKind::SymActions
+            )
+        })
+        .enumerate()
+    {
+        let Ok(range) = char_range_from_byte_range(slide.range(), current_rope) else {
+            continue;
+        };
+        let arguments = Some(vec![
+            serde_json::to_value(currently_open).ok()?,
+            index.into(),
+        ]);
+
+        for (title, command) in [
+            ("Present from here", PRESENT_COMMAND),
+            ("Export this slide", EXPORT_COMMAND),
+        ] {
+            lenses.push(CodeLens {
+                range,
+                command: Some(Command {
+                    title: title.to_string(),
+                    command: command.to_string(),
+                    arguments: arguments.clone(),
+                }),
+                data: None,
+            });
+        }
+    }
+
+    Some(lenses)
+}
@@ -0,0 +1,177 @@
+use std::str::FromStr;
+
+use css_color::Srgb;
+use egui::Color32;
+use grezi_parser::parse::slideshow::actions::ACTION_SIGNATURES;
+use helix_lsp_types as lsp_types;
+use lsp_types::{Color, ColorInformation, ColorPresentation, ColorPresentationParams, TextEdit};
+use ropey::Rope;
+use tree_sitter::{Node, Tree};
+use tree_sitter_grz::{FieldName, NodeKind};
+
+use super::{
+    formatter::{byte_range_from_char_range, char_range_from_byte_range},
+    hover::color_to_hex,
+};
+
+/// Object parameters and registers that are parsed with `parse_color`
+const COLOR_KEYS: &[&str] = &["color", "stroke", "tint", "BACKGROUND"];
+
+pub fn document_colors(current_rope: &Rope, tree: &Option<Tree>) -> Option<Vec<ColorInformation>> {
+    let tree = tree.as_ref()?;
+    let mut colors = Vec::new();
+    find_colors(tree.root_node(), current_rope, &mut colors);
+    Some(colors)
+}
+
+pub fn color_presentations(
+    params: ColorPresentationParams,
+    current_rope: &Rope,
+    tree: &Option<Tree>,
+) -> Vec<ColorPresentation> {
+    let Color {
+        red,
+        green,
+        blue,
+        alpha,
+    } = params.color;
+    let [r, g, b, a] = [red, green, blue, alpha].map(|c| (c * 255.0).round() as u8);
+
+    let hex = color_to_hex(Color32::from_rgba_unmultiplied(r, g, b, a));
+    let rgb = if a == u8::MAX {
+        format!("rgb({}, {}, {})", r, g, b)
+    } else {
+        format!(
+            "rgba({}, {}, {}, {})",
+            r,
+            g,
+            b,
+            (alpha * 100.0).round() / 100.0
+        )
+    };
+
+    // Action arguments can't be `obj_other`s, so they
+    // always need to be string literals
+    let quoted = byte_range_from_char_range(params.range, current_rope)
+        .ok()
+        .and_then(|range| {
+            let mut node = tree
+                .as_ref()?
+                .root_node()
+                .descendant_for_point_range(range.start_point, range.end_point)?;
+            loop {
+                match NodeKind::from(node.kind_id()) {
+                    NodeKind::SymStringLiteral | NodeKind::SymSlideFunction => return Some(true),
+                    NodeKind::SymObjParam => return Some(false),
+                    _ => node = node.parent()?,
+                }
+            }
+        })
+        .unwrap_or(true);
+
+    [hex, rgb]
+        .into_iter()
+        .map(|label| ColorPresentation {
+            text_edit: Some(TextEdit {
+                range: params.range,
+                new_text: if quoted {
+                    format!("\"{}\"", label)
+                } else {
+                    label.clone()
+                },
+            }),
+            label,
+            additional_text_edits: None,
+        })
+        .collect()
+}
+
+fn find_colors(node: Node, current_rope: &Rope, colors: &mut Vec<ColorInformation>) {
+    match NodeKind::from(node.kind_id()) {
+        NodeKind::SymObjParam => {
+            if let Some(key) = node.child_by_field_id(FieldName::FieldKey as u16) {
+                if COLOR_KEYS
+                    .iter()
+                    .any(|k| current_rope.byte_slice(key.byte_range()) == *k)
+                {
+                    if let Some(value) = node.child_by_field_id(FieldName::FieldValue as u16) {
+                        push_color(value, current_rope, colors);
+                    }
+                }
+            }
+            return;
+        }
+        NodeKind::SymSlideFunction => {
+            let Some(function) = node.child_by_field_id(FieldName::FieldFunction as u16) else {
+                return;
+            };
+            let function = current_rope.byte_slice(function.byte_range());
+            // Actions that take a color have a `color` parameter
+            if let Some(index) = ACTION_SIGNATURES
+                .iter()
+                .find(|signature| function == signature.name)
+                .and_then(|signature| {
+                    signature
+                        .parameters
+                        .iter()
+                        .position(|(parameter, _)| *parameter == "color")
+                })
+            {
+                let mut cursor = node.walk();
+                if let Some(argument) = node
+                    .named_children(&mut cursor)
+                    .filter(|n| {
+                        !matches!(
+                            NodeKind::from(n.kind_id()),
+                            NodeKind::SymWhitespace | NodeKind::SymComment
+                        )
+                    })
+                    // Skip the function name
+                    .nth(index + 1)
+                {
+                    push_color(argument, current_rope, colors);
+                }
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        find_colors(child, current_rope, colors);
+    }
+}
+
+fn push_color(node: Node, current_rope: &Rope, colors: &mut Vec<ColorInformation>) {
+    let text = current_rope.byte_slice(node.byte_range()).to_string();
+    let Ok(color) = Srgb::from_str(literal_contents(&text)) else {
+        return;
+    };
+    let Ok(range) = char_range_from_byte_range(node.range(), current_rope) else {
+        return;
+    };
+
+    colors.push(ColorInformation {
+        range,
+        color: Color {
+            red: color.red,
+            green: color.green,
+            blue: color.blue,
+            alpha: color.alpha,
+        },
+    });
+}
+
+/// Strips the quotes off of a string literal,
+/// leaving anything else untouched
+fn literal_contents(text: &str) -> &str {
+    text.strip_prefix("r#\"")
+        .and_then(|t| t.strip_suffix("\"#"))
+        .or_else(|| {
+            text.strip_prefix('"')
+                .or_else(|| text.strip_prefix("b\""))
+                .and_then(|t| t.strip_suffix('"'))
+        })
+        .unwrap_or(text)
+}
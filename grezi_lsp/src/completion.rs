@@ -0,0 +1,453 @@
+use grezi_parser::{
+    GrzRoot,
+    object::ObjInner,
+    parse::{byte_pos_from_char_pos, slideshow::actions::ACTION_SIGNATURES},
+    text::TextSection,
+};
+use helix_lsp_types as lsp_types;
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionList, CompletionParams, CompletionResponse,
+    Documentation,
+};
+use nucleo_matcher::{
+    Matcher,
+    pattern::{AtomKind, CaseMatching, Normalization, Pattern},
+};
+use ropey::Rope;
+use tree_sitter::{Node, Point, Tree};
+use tree_sitter_grz::{FieldName, NodeKind};
+
+/// Object types understood by `ObjInner::parse`, and `Style`
+pub const OBJECT_TYPES: &[(&str, &str)] = &[
+    ("Rect", "A filled rectangle with an optional stroke"),
+    (
+        "Image",
+        "An image loaded from a path relative to the GRZ file",
+    ),
+    (
+        "Header",
+        "Djot formatted text with a default font size of 64",
+    ),
+    (
+        "Paragraph",
+        "Djot formatted text with a default font size of 48",
+    ),
+    (
+        "Code",
+        "Source code with one paragraph per line, line numbers and diffs",
+    ),
+    (
+        "Style",
+        "Text properties used by text objects with `style`, and by djot with `[text]{.Name}`",
+    ),
+];
+
+pub const RECT_PARAMS: &[(&str, &str)] = &[
+    ("color", "Fill color of the rectangle"),
+    ("stroke", "Color of the rectangle's outline"),
+    (
+        "height",
+        "Height of the rectangle as a fraction of its viewbox",
+    ),
+];
+
+pub const IMAGE_PARAMS: &[(&str, &str)] = &[
+    ("value", "Path to the image, relative to the GRZ file"),
+    ("tint", "Color the image is multiplied by"),
+    (
+        "scale",
+        "Fixed size of the image, instead of fitting it to its viewbox",
+    ),
+];
+
+pub const TEXT_PARAMS: &[(&str, &str)] = &[
+    ("value", "The text to display, formatted with djot"),
+    (
+        "code",
+        "Source code to display, highlighted with `language`",
+    ),
+    ("language", "Language used to syntax highlight `code`"),
+    (
+        "code_theme",
+        "Helix theme used to highlight code, by name or as a path to a TOML file",
+    ),
+    (
+        "align",
+        "One of `left`, `center`, `right`, `justified` or `end`",
+    ),
+    ("color", "Default color of the text"),
+    ("font_family", "Default font family of the text"),
+    ("font_size", "Default font size of the text"),
+    (
+        "style",
+        "Name of a style declared before this object, which the other parameters override",
+    ),
+    ("line_height", "Height of each line of text, in pixels"),
+    (
+        "tagged",
+        "Whether each paragraph is tagged in exported PDFs",
+    ),
+];
+
+pub const CODE_PARAMS: &[(&str, &str)] = &[
+    (
+        "code",
+        "Source code to display, highlighted with `language`",
+    ),
+    ("language", "Language used to syntax highlight `code`"),
+    (
+        "code_theme",
+        "Helix theme used to highlight code, by name or as a path to a TOML file",
+    ),
+    ("line_numbers", "Whether each line is numbered"),
+    ("start_line", "The number of the first line, 1 by default"),
+    (
+        "highlight_lines",
+        "Lines to keep bright while the rest are faded, like `2-4, 7`",
+    ),
+    (
+        "diff",
+        "Whether lines starting with `+` or `-` are added or removed",
+    ),
+    ("color", "Default color of the code"),
+    (
+        "font_family",
+        "Font family of the code, monospace by default",
+    ),
+    ("font_size", "Font size of the code"),
+    (
+        "style",
+        "Name of a style declared before this object, which the other parameters override",
+    ),
+    ("line_height", "Height of each line of code, in pixels"),
+    ("tagged", "Whether each line is tagged in exported PDFs"),
+];
+
+pub const STYLE_PARAMS: &[(&str, &str)] = &[
+    (
+        "font_family",
+        "Font family, with modifiers like `Inter:bold:italic`",
+    ),
+    (
+        "font_size",
+        "Font size of text objects and djot blocks, not spans",
+    ),
+    ("color", "Color of the text"),
+    ("weight", "A weight from 100 to 900, or a name like `bold`"),
+    (
+        "line_height",
+        "Height of each line of text objects, in pixels",
+    ),
+    (
+        "align",
+        "Alignment of text objects, one of `left`, `center`, `right`, `justified` or `end`",
+    ),
+];
+
+pub fn params_for_type(ty: &str) -> Option<&'static [(&'static str, &'static str)]> {
+    match ty {
+        "Rect" => Some(RECT_PARAMS),
+        "Image" => Some(IMAGE_PARAMS),
+        "Header" | "Paragraph" => Some(TEXT_PARAMS),
+        "Code" => Some(CODE_PARAMS),
+        "Style" => Some(STYLE_PARAMS),
+        _ => None,
+    }
+}
+
+/// Looks up the documentation of a keyword in one of the tables above
+pub fn documentation(table: &[(&str, &'static str)], keyword: &str) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(name, _)| *name == keyword)
+        .map(|(_, documentation)| *documentation)
+}
+
+/// Registers understood by `Registers::parse`
+pub const REGISTERS: &[(&str, &str)] = &[
+    (
+        "MARGIN",
+        "Margin, in pixels, between the boxes of a viewbox",
+    ),
+    (
+        "MARGIN_PER",
+        "Margin between the boxes of a viewbox, as a fraction of the viewbox",
+    ),
+    ("BACKGROUND", "Background color of the following slides"),
+    (
+        "CREATE_EDGES",
+        "Whether objects enter and exit from the edges of the screen",
+    ),
+    ("FLEX", "Layout algorithm used to split viewboxes"),
+    (
+        "INCLUDE",
+        "Splices in the viewboxes, objects and registers of another GRZ file, relative to this one",
+    ),
+    (
+        "THEME",
+        "Colors, fonts, sizes and margins of the following slides, from a TOML file relative to this one",
+    ),
+];
+
+/// Where the cursor is, as far as completion is concerned
+enum CompletionContext<'a> {
+    ObjectType,
+    ObjectParam(ObjectParams<'a>),
+    Action,
+    Register,
+    SlideObject,
+    Viewbox,
+    TopLevel,
+}
+
+enum ObjectParams<'a> {
+    /// Parameters of an object type written in the source
+    Type(String),
+    /// Parameters of an object already parsed into the root
+    Object(&'a ObjInner),
+}
+
+struct MatchableCompletionItem(CompletionItem);
+
+impl AsRef<str> for MatchableCompletionItem {
+    fn as_ref(&self) -> &str {
+        self.0.label.as_str()
+    }
+}
+
+pub fn completion(
+    params: CompletionParams,
+    current_rope: &Rope,
+    tree: &Option<Tree>,
+    root: &GrzRoot,
+) -> Option<CompletionResponse> {
+    let tree = tree.as_ref()?;
+    let position = params.text_document_position.position;
+    let line = current_rope.get_line(position.line as usize)?;
+    let cursor_char = (position.character as usize).min(line.len_chars());
+
+    let prefix_len = line
+        .chars_at(cursor_char)
+        .reversed()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .count();
+    let prefix = line
+        .slice(cursor_char - prefix_len..cursor_char)
+        .to_string();
+
+    // `descendant_for_point_range` won't return a node that
+    // ends at the point, so we look at the start of the word
+    // being typed, or the character right before the cursor
+    let lookup_char = if prefix_len > 0 {
+        cursor_char - prefix_len
+    } else {
+        cursor_char.saturating_sub(1)
+    };
+    let point = byte_pos_from_char_pos((position.line as usize, lookup_char), current_rope).ok()?;
+    let byte = current_rope.try_line_to_byte(point.row).ok()? + point.column;
+
+    let node = tree.root_node().descendant_for_point_range(point, point)?;
+
+    let mut items = Vec::new();
+    match completion_context(node, byte, point, current_rope, root)? {
+        CompletionContext::ObjectType => {
+            push_items(&mut items, OBJECT_TYPES, CompletionItemKind::CLASS);
+            // Objects can derive from other objects
+            push_object_names(&mut items, root);
+        }
+        CompletionContext::ObjectParam(ObjectParams::Type(ty)) => {
+            if let Some(params) = params_for_type(&ty).or_else(|| {
+                root.objects
+                    .get(ty.as_str())
+                    .and_then(|object| params_for_type(&object.declaration.ty))
+            }) {
+                push_items(&mut items, params, CompletionItemKind::FIELD)
+            }
+        }
+        CompletionContext::ObjectParam(ObjectParams::Object(parameters)) => push_items(
+            &mut items,
+            match parameters {
+                ObjInner::Rect { .. } => RECT_PARAMS,
+                ObjInner::Image { .. } => IMAGE_PARAMS,
+                ObjInner::Text { job, .. } if matches!(job.first(), Some(TextSection::Code(_))) => {
+                    CODE_PARAMS
+                }
+                ObjInner::Text { .. } => TEXT_PARAMS,
+            },
+            CompletionItemKind::FIELD,
+        ),
+        CompletionContext::Action => {
+            items.extend(ACTION_SIGNATURES.iter().map(|signature| CompletionItem {
+                label: signature.name.to_string(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some(signature.label()),
+                documentation: Some(Documentation::String(signature.documentation.to_string())),
+                ..Default::default()
+            }))
+        }
+        CompletionContext::Register => {
+            push_items(&mut items, REGISTERS, CompletionItemKind::CONSTANT)
+        }
+        CompletionContext::SlideObject => push_object_names(&mut items, root),
+        CompletionContext::Viewbox => push_viewbox_names(&mut items, root),
+        CompletionContext::TopLevel => {
+            push_object_names(&mut items, root);
+            push_viewbox_names(&mut items, root);
+            push_items(&mut items, OBJECT_TYPES, CompletionItemKind::CLASS);
+        }
+    }
+
+    let mut matcher = Matcher::new(nucleo_matcher::Config::DEFAULT);
+    let matches = Pattern::new(
+        &prefix,
+        CaseMatching::Ignore,
+        Normalization::Smart,
+        AtomKind::Fuzzy,
+    )
+    .match_list(items.into_iter().map(MatchableCompletionItem), &mut matcher);
+
+    let items = matches
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (item, _))| CompletionItem {
+            // Keep the order nucleo ranked the items in,
+            // instead of letting the editor filter and sort them
+            sort_text: Some(format!("{:04}", rank)),
+            filter_text: Some(prefix.clone()),
+            ..item.0
+        })
+        .collect::<Vec<_>>();
+
+    if items.is_empty() {
+        return None;
+    }
+
+    Some(CompletionResponse::List(CompletionList {
+        // Items are filtered with the word being typed,
+        // so they need to be requested again as it changes
+        is_incomplete: true,
+        items,
+    }))
+}
+
+fn completion_context<'a>(
+    mut node: Node,
+    byte: usize,
+    point: Point,
+    current_rope: &Rope,
+    root: &'a GrzRoot,
+) -> Option<CompletionContext<'a>> {
+    loop {
+        match NodeKind::from(node.kind_id()) {
+            NodeKind::SymObjInner => {
+                if field_contains(node, FieldName::FieldTy, byte) {
+                    return Some(CompletionContext::ObjectType);
+                }
+                let ty = node.child_by_field_id(FieldName::FieldTy as u16)?;
+                return Some(CompletionContext::ObjectParam(ObjectParams::Type(
+                    current_rope.byte_slice(ty.byte_range()).to_string(),
+                )));
+            }
+            NodeKind::SymObjParam => {
+                // Only the keys of parameters are completed
+                if field_contains(node, FieldName::FieldValue, byte) {
+                    return None;
+                }
+            }
+            NodeKind::SymObj | NodeKind::SymViewbox => {
+                if field_contains(node, FieldName::FieldName, byte) {
+                    return None;
+                }
+                return Some(if NodeKind::from(node.kind_id()) == NodeKind::SymObj {
+                    CompletionContext::ObjectType
+                } else {
+                    CompletionContext::Viewbox
+                });
+            }
+            NodeKind::SymRegister => return Some(CompletionContext::Register),
+            NodeKind::SymSlideFunction => {
+                if field_contains(node, FieldName::FieldFunction, byte) {
+                    return Some(CompletionContext::Action);
+                }
+                return Some(CompletionContext::SlideObject);
+            }
+            NodeKind::SymSlideFunctions => return Some(CompletionContext::Action),
+            NodeKind::SymVbRef | NodeKind::SymSlideVb => {
+                return Some(CompletionContext::Viewbox);
+            }
+            NodeKind::SymSlideObj | NodeKind::SymSlideObjects => {
+                return Some(CompletionContext::SlideObject);
+            }
+            NodeKind::SymCompletion => {
+                // `Object.` completes the parameters of `Object`
+                let object = node.named_child(0)?;
+                if object.start_position() <= point && object.end_position() > point {
+                    return Some(CompletionContext::SlideObject);
+                }
+                let object_name = current_rope.byte_slice(object.byte_range()).to_string();
+                return root.objects.get(object_name.as_str()).map(|object| {
+                    CompletionContext::ObjectParam(ObjectParams::Object(&object.parameters))
+                });
+            }
+            NodeKind::SymSourceFile => return Some(CompletionContext::TopLevel),
+            _ => {}
+        }
+
+        node = match node.parent() {
+            Some(parent) => parent,
+            None => return Some(CompletionContext::TopLevel),
+        };
+    }
+}
+
+fn field_contains(node: Node, field: FieldName, byte: usize) -> bool {
+    node.child_by_field_id(field as u16)
+        .map(|field| field.byte_range().contains(&byte))
+        .unwrap_or_default()
+}
+
+fn push_items(items: &mut Vec<CompletionItem>, table: &[(&str, &str)], kind: CompletionItemKind) {
+    items.extend(table.iter().map(|(label, documentation)| CompletionItem {
+        label: label.to_string(),
+        kind: Some(kind),
+        documentation: Some(Documentation::String(documentation.to_string())),
+        ..Default::default()
+    }));
+}
+
+fn push_object_names(items: &mut Vec<CompletionItem>, root: &GrzRoot) {
+    items.extend(root.objects.iter().map(|(name, object)| {
+        CompletionItem {
+            label: name.to_string(),
+            kind: Some(CompletionItemKind::VARIABLE),
+            detail: Some(
+                match object.parameters {
+                    ObjInner::Text { .. } => "Text",
+                    ObjInner::Image { .. } => "Image",
+                    ObjInner::Rect { .. } => "Rect",
+                }
+                .to_string(),
+            ),
+            ..Default::default()
+        }
+    }));
+}
+
+fn push_viewbox_names(items: &mut Vec<CompletionItem>, root: &GrzRoot) {
+    items.push(CompletionItem {
+        label: "Size".to_string(),
+        kind: Some(CompletionItemKind::CONSTANT),
+        detail: Some("The whole screen".to_string()),
+        ..Default::default()
+    });
+    items.extend(
+        root.viewboxes
+            .iter()
+            .map(|(name, (split, _))| CompletionItem {
+                label: name.to_string(),
+                kind: Some(CompletionItemKind::STRUCT),
+                detail: Some(format!("Viewbox with {} boxes", split.len())),
+                ..Default::default()
+            }),
+    );
+}
@@ -67,19 +67,7 @@ pub fn format_code(current_rope: &Rope, tree: &Tree) -> Result<Vec<TextEdit>, ()
     )?;
 
     loop {
-        let node = formatting_cursor.node();
-        let node_kind = NodeKind::from(node.kind_id());
-        match node_kind {
-            NodeKind::SymObj => object::format_object(current_rope, &mut formatting_cursor)?,
-            NodeKind::SymRegister => {
-                registers::format_registers(current_rope, &mut formatting_cursor)?
-            }
-            NodeKind::SymViewbox => viewbox::format_viewbox(current_rope, &mut formatting_cursor)?,
-            NodeKind::SymSlide => slide::format_slide(current_rope, &mut formatting_cursor)?,
-            // NodeKind::SymSlide => {}
-            NodeKind::SymActions => actions::format_actions(current_rope, &mut formatting_cursor)?,
-            _ => return Err(()),
-        }
+        format_definition(current_rope, &mut formatting_cursor)?;
 
         let result =
             formatting_cursor.goto_next_sibling(WhitespaceEdit::Assert("\n\n"), current_rope)?;
@@ -130,6 +118,80 @@ pub fn format_code(current_rope: &Rope, tree: &Tree) -> Result<Vec<TextEdit>, ()
     Ok(formatting_cursor.edits)
 }
 
+fn format_definition(current_rope: &Rope, cursor: &mut FormattingCursor) -> Result<(), ()> {
+    match NodeKind::from(cursor.node().kind_id()) {
+        NodeKind::SymObj => object::format_object(current_rope, cursor),
+        NodeKind::SymRegister => registers::format_registers(current_rope, cursor),
+        NodeKind::SymViewbox => viewbox::format_viewbox(current_rope, cursor),
+        NodeKind::SymSlide => slide::format_slide(current_rope, cursor),
+        NodeKind::SymActions => actions::format_actions(current_rope, cursor),
+        _ => Err(()),
+    }
+}
+
+/// Formats only the top level definitions that overlap `range`,
+/// without touching the whitespace between them
+#[instrument(skip_all)]
+pub fn format_range(
+    current_rope: &Rope,
+    tree: &Tree,
+    range: tree_sitter::Range,
+) -> Result<Vec<TextEdit>, ()> {
+    let mut edits = Vec::new();
+    let mut tree_cursor = tree.walk();
+    for definition in tree
+        .root_node()
+        .named_children(&mut tree_cursor)
+        .filter(|n| !n.is_extra())
+        .filter(|n| n.end_byte() >= range.start_byte && n.start_byte() <= range.end_byte)
+    {
+        let mut formatting_cursor = FormattingCursor::from_node(definition);
+        format_definition(current_rope, &mut formatting_cursor)?;
+        edits.append(&mut formatting_cursor.edits);
+    }
+
+    Ok(edits)
+}
+
+/// Formats a standalone piece of GRZ source, like a definition
+/// generated by a code action. If the snippet can't be parsed,
+/// it's returned untouched
+#[instrument(skip_all)]
+pub fn format_snippet(snippet: String) -> String {
+    let mut parser = tree_sitter::Parser::new();
+    if parser
+        .set_language(&tree_sitter_grz::LANGUAGE.into())
+        .is_err()
+    {
+        return snippet;
+    }
+    let Some(tree) = parser.parse(&snippet, None) else {
+        return snippet;
+    };
+    let mut rope = Rope::from_str(&snippet);
+    let Ok(mut edits) = format_code(&rope, &tree) else {
+        return snippet;
+    };
+
+    // Apply the edits back to front so the earlier
+    // positions stay valid
+    edits.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+    for edit in edits.into_iter().rev() {
+        let char_index = |pos: Position| {
+            rope.try_line_to_char(pos.line as usize)
+                .map(|line| line + pos.character as usize)
+        };
+        let (Ok(start), Ok(end)) = (char_index(edit.range.start), char_index(edit.range.end))
+        else {
+            return snippet;
+        };
+        rope.remove(start..end);
+        rope.insert(start, &edit.new_text);
+    }
+
+    rope.to_string()
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum WhitespaceEdit {
     Delete,
@@ -154,6 +216,16 @@ impl<'a> FormattingCursor<'a> {
         }
     }
 
+    /// A cursor that only formats `node` and its children
+    pub fn from_node(node: Node<'a>) -> FormattingCursor<'a> {
+        FormattingCursor {
+            tree_cursor: node.walk(),
+            edits: Vec::new(),
+            last_range: node.range(),
+            edited: false,
+        }
+    }
+
     fn check_for_error(&self, result: bool) -> Result<bool, ()> {
         if !result {
             return Ok(false);
@@ -0,0 +1,282 @@
+use std::fmt::Write;
+
+use egui::{Color32, Rect};
+use grezi_parser::{
+    GrzRoot,
+    object::ObjInner,
+    parse::{byte_pos_from_char_pos, slideshow::actions::ActionSignature},
+    slide::BASE_SIZE,
+    text::TextSection,
+};
+use helix_lsp_types as lsp_types;
+use lsp_types::{Hover, HoverContents, HoverParams, MarkupContent, MarkupKind};
+use ropey::Rope;
+use tree_sitter::{Node, Tree};
+use tree_sitter_grz::{FieldName, NodeKind};
+
+use super::{
+    completion::{self, OBJECT_TYPES, REGISTERS},
+    formatter::char_range_from_byte_range,
+};
+
+pub fn hover(
+    params: HoverParams,
+    current_rope: &Rope,
+    tree: &Option<Tree>,
+    root: &GrzRoot,
+) -> Option<Hover> {
+    let tree = tree.as_ref()?;
+    let position = params.text_document_position_params.position;
+    let point = byte_pos_from_char_pos(
+        (position.line as usize, position.character as usize),
+        current_rope,
+    )
+    .ok()?;
+    let byte = current_rope.try_line_to_byte(point.row).ok()? + point.column;
+
+    let node = tree.root_node().descendant_for_point_range(point, point)?;
+    let node_text = |node: Node| current_rope.byte_slice(node.byte_range()).to_string();
+
+    let mut ancestor = node;
+    let (hovered, value) = loop {
+        match NodeKind::from(ancestor.kind_id()) {
+            NodeKind::SymVbRef => {
+                let viewbox = ancestor.child_by_field_id(FieldName::FieldViewbox as u16)?;
+                let subbox = ancestor
+                    .child_by_field_id(FieldName::FieldViewboxIndex as u16)
+                    .and_then(|index| index.named_child(0))
+                    .and_then(|index| node_text(index).parse::<usize>().ok());
+                break (ancestor, viewbox_hover(&node_text(viewbox), subbox, root)?);
+            }
+            NodeKind::SymSlideFunction => {
+                let function = ancestor.child_by_field_id(FieldName::FieldFunction as u16)?;
+                if function.byte_range().contains(&byte) {
+                    let signature = ActionSignature::get(&node_text(function))?;
+                    break (
+                        function,
+                        format!(
+                            "```grz\n{}\n```\n{}",
+                            signature.label(),
+                            signature.documentation
+                        ),
+                    );
+                } else if NodeKind::from(node.kind_id()) == NodeKind::SymIdentifier {
+                    break (node, object_hover(&node_text(node), root)?);
+                }
+                return None;
+            }
+            NodeKind::SymObjParam => {
+                let key = ancestor.child_by_field_id(FieldName::FieldKey as u16)?;
+                if !key.byte_range().contains(&byte) {
+                    return None;
+                }
+                let key_text = node_text(key);
+                let parent = ancestor.parent()?;
+                let documentation = match NodeKind::from(parent.kind_id()) {
+                    NodeKind::SymRegister => completion::documentation(REGISTERS, &key_text)?,
+                    NodeKind::SymObjInner => {
+                        let ty_text =
+                            node_text(parent.child_by_field_id(FieldName::FieldTy as u16)?);
+                        // Objects that derive from other objects
+                        // take the parameters of their built in type
+                        let params = completion::params_for_type(&ty_text).or_else(|| {
+                            completion::params_for_type(
+                                &root.objects.get(ty_text.as_str())?.declaration.ty,
+                            )
+                        })?;
+                        completion::documentation(params, &key_text)?
+                    }
+                    _ => return None,
+                };
+                break (key, format!("`{}`\n\n{}", key_text, documentation));
+            }
+            NodeKind::SymObjInner => {
+                let ty = ancestor.child_by_field_id(FieldName::FieldTy as u16)?;
+                if !ty.byte_range().contains(&byte) {
+                    return None;
+                }
+                let ty_text = node_text(ty);
+                break (
+                    ty,
+                    match completion::documentation(OBJECT_TYPES, &ty_text) {
+                        Some(documentation) => format!("`{}`\n\n{}", ty_text, documentation),
+                        None => object_hover(&ty_text, root)?,
+                    },
+                );
+            }
+            NodeKind::SymObj | NodeKind::SymViewbox | NodeKind::SymSlideObj => {
+                let field = if NodeKind::from(ancestor.kind_id()) == NodeKind::SymSlideObj {
+                    FieldName::FieldObject
+                } else {
+                    FieldName::FieldName
+                };
+                let name = ancestor.child_by_field_id(field as u16)?;
+                if !name.byte_range().contains(&byte) {
+                    return None;
+                }
+                let name_text = node_text(name);
+                break (
+                    name,
+                    if NodeKind::from(ancestor.kind_id()) == NodeKind::SymViewbox {
+                        viewbox_hover(&name_text, None, root)?
+                    } else {
+                        object_hover(&name_text, root)?
+                    },
+                );
+            }
+            NodeKind::SymSourceFile => return None,
+            _ => {}
+        }
+
+        ancestor = ancestor.parent()?;
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: char_range_from_byte_range(hovered.range(), current_rope).ok(),
+    })
+}
+
+fn object_hover(name: &str, root: &GrzRoot) -> Option<String> {
+    let object = root.objects.get(name)?;
+    let mut hover = String::new();
+    match &object.parameters {
+        ObjInner::Text {
+            job,
+            line_height,
+            align,
+        } => {
+            writeln!(hover, "`{}`: Text\n", name).ok()?;
+            writeln!(hover, "- align: `{:?}`", align).ok()?;
+            if let Some(line_height) = line_height {
+                writeln!(hover, "- line_height: `{}`", line_height).ok()?;
+            }
+            let mut preview = String::new();
+            text_preview(job, &mut preview);
+            writeln!(hover, "\n```\n{}\n```", preview.trim_end()).ok()?;
+        }
+        ObjInner::Image {
+            url, scale, tint, ..
+        } => {
+            writeln!(hover, "`{}`: Image\n", name).ok()?;
+            writeln!(hover, "- value: `{}`", url.path()).ok()?;
+            if let Some(scale) = scale {
+                writeln!(hover, "- scale: `{}`", scale).ok()?;
+            }
+            writeln!(hover, "- tint: `{}`", color_to_hex(*tint)).ok()?;
+        }
+        ObjInner::Rect {
+            color,
+            stroke,
+            height,
+        } => {
+            writeln!(hover, "`{}`: Rect\n", name).ok()?;
+            writeln!(hover, "- color: `{}`", color_to_hex(*color)).ok()?;
+            writeln!(hover, "- stroke: `{}`", color_to_hex(*stroke)).ok()?;
+            writeln!(hover, "- height: `{}`", height).ok()?;
+        }
+    }
+
+    Some(hover)
+}
+
+fn viewbox_hover(name: &str, subbox: Option<usize>, root: &GrzRoot) -> Option<String> {
+    let split: &[Rect] = if name == "Size" || name == "Screen" {
+        std::slice::from_ref(&BASE_SIZE)
+    } else {
+        root.viewboxes.get(name)?.0.as_slice()
+    };
+
+    let mut hover = String::new();
+    match subbox {
+        Some(subbox) => {
+            writeln!(
+                hover,
+                "`{}[{}]`: {}",
+                name,
+                subbox,
+                format_rect(split.get(subbox)?)
+            )
+            .ok()?;
+        }
+        None => {
+            writeln!(hover, "`{}`\n", name).ok()?;
+            for (subbox, rect) in split.iter().enumerate() {
+                writeln!(hover, "- `[{}]`: {}", subbox, format_rect(rect)).ok()?;
+            }
+        }
+    }
+
+    Some(hover)
+}
+
+/// Formats a rect the same way a `vb_rect` is written
+/// in a GRZ file, along with its size
+fn format_rect(rect: &Rect) -> String {
+    let round = |n: f32| (n * 100.0).round() / 100.0;
+    format!(
+        "`[[{} {}] - [{} {}]]` ({}x{})",
+        round(rect.min.x),
+        round(rect.min.y),
+        round(rect.max.x),
+        round(rect.max.y),
+        round(rect.width()),
+        round(rect.height())
+    )
+}
+
+fn text_preview(job: &[TextSection], preview: &mut String) {
+    for section in job {
+        match section {
+            TextSection::Paragraph(paragraph) => {
+                for (text, _) in &paragraph.rich_text {
+                    preview.push_str(text);
+                }
+                preview.push('\n');
+            }
+            TextSection::Blockquote(job) => text_preview(job, preview),
+            TextSection::List(list) => {
+                for (marker, item) in list {
+                    for (text, _) in &marker.rich_text {
+                        preview.push_str(text);
+                    }
+                    preview.push(' ');
+                    text_preview(item, preview);
+                }
+            }
+            TextSection::Code(lines) => {
+                for (_, line) in lines {
+                    for (text, _) in &line.rich_text {
+                        preview.push_str(text);
+                    }
+                    preview.push('\n');
+                }
+            }
+            TextSection::Table(table) => {
+                for row in &table.rows {
+                    for (index, cell) in row.cells.iter().enumerate() {
+                        if index != 0 {
+                            preview.push_str(" | ");
+                        }
+                        for (text, _) in &cell.rich_text {
+                            preview.push_str(text);
+                        }
+                    }
+                    preview.push('\n');
+                }
+            }
+        }
+    }
+}
+
+pub fn color_to_hex(color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    if a == u8::MAX {
+        format!("#{:02X}{:02X}{:02X}", r, g, b)
+    } else {
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+    }
+}
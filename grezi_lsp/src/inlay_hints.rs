@@ -0,0 +1,103 @@
+use grezi_parser::GrzRoot;
+use helix_lsp_types as lsp_types;
+use lsp_types::{InlayHint, InlayHintLabel, InlayHintParams};
+use ropey::Rope;
+use tree_sitter::{Node, Tree};
+use tree_sitter_grz::{FieldName, NodeKind};
+
+use super::formatter::{byte_range_from_char_range, char_range_from_byte_range};
+
+/// Numbers every slide starting from 1, and shows the
+/// size every viewbox constraint resolved to
+pub fn inlay_hints(
+    params: InlayHintParams,
+    current_rope: &Rope,
+    tree: &Option<Tree>,
+    root: &GrzRoot,
+) -> Option<Vec<InlayHint>> {
+    let tree = tree.as_ref()?;
+    let visible = byte_range_from_char_range(params.range, current_rope).ok()?;
+    let mut hints = Vec::new();
+
+    let mut slide_index = 0;
+    let mut cursor = tree.walk();
+    for definition in tree.root_node().named_children(&mut cursor) {
+        let kind = NodeKind::from(definition.kind_id());
+        if matches!(kind, NodeKind::SymSlide | NodeKind::SymActions) {
+            slide_index += 1;
+        }
+        if definition.end_byte() < visible.start_byte || definition.start_byte() > visible.end_byte
+        {
+            continue;
+        }
+
+        match kind {
+            NodeKind::SymSlide | NodeKind::SymActions => {
+                hints.extend(hint(
+                    definition,
+                    false,
+                    format!("#{}", slide_index),
+                    current_rope,
+                ));
+            }
+            NodeKind::SymViewbox => viewbox_hints(definition, current_rope, root, &mut hints),
+            _ => {}
+        }
+    }
+
+    Some(hints)
+}
+
+fn viewbox_hints(viewbox: Node, current_rope: &Rope, root: &GrzRoot, hints: &mut Vec<InlayHint>) {
+    let Some(name) = viewbox.child_by_field_id(FieldName::FieldName as u16) else {
+        return;
+    };
+    let name: String = current_rope.byte_slice(name.byte_range()).into();
+    let Some((split, _)) = root.viewboxes.get(name.as_str()) else {
+        return;
+    };
+    let Some(body) = viewbox.child_by_field_id(FieldName::FieldBody as u16) else {
+        return;
+    };
+
+    let mut cursor = body.walk();
+    for (viewbox_obj, rect) in body
+        .named_children(&mut cursor)
+        .filter(|n| NodeKind::from(n.kind_id()) == NodeKind::SymViewboxObj)
+        .zip(split)
+    {
+        let round = |n: f32| (n * 100.0).round() / 100.0;
+        hints.extend(hint(
+            viewbox_obj,
+            true,
+            format!("{}x{}", round(rect.width()), round(rect.height())),
+            current_rope,
+        ));
+    }
+}
+
+/// Places a hint either before the node, or after the
+/// separator that follows it
+fn hint(node: Node, after: bool, label: String, current_rope: &Rope) -> Option<InlayHint> {
+    let mut range = node.range();
+    if after {
+        // Put the hint after the comma, if there is one
+        if let Some(separator) = node.next_sibling().filter(|n| !n.is_named()) {
+            range = separator.range();
+        }
+        range.start_byte = range.end_byte;
+        range.start_point = range.end_point;
+    }
+    let position = char_range_from_byte_range(range, current_rope).ok()?.start;
+
+    Some(InlayHint {
+        position,
+        label: InlayHintLabel::String(label),
+        kind: None,
+        text_edits: None,
+        tooltip: None,
+        padding_left: Some(after),
+        padding_right: Some(!after),
+        data: None,
+    })
+}
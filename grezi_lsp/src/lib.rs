@@ -3,36 +3,54 @@ use std::{collections::HashMap, ops::DerefMut, str::FromStr, sync::Arc};
 use crossbeam_channel::Receiver;
 use egui::Modifiers;
 use grezi_egui::GrzResolvedSlide;
+use grezi_export::GrzExporter;
 use grezi_file_owner::{AppHandle, FileOwnerMessage};
-use grezi_parser::parse::{GrzFile, error::ErrsWithSource};
+use grezi_parser::{
+    parse::{GrzFile, error::ErrsWithSource, slideshow::include},
+    slide::BASE_SIZE,
+};
 use helix_core::syntax::generate_edits;
 use helix_lsp::{Position, Url};
 use helix_lsp_types::{
-    self as lsp_types, DeclarationCapability, DocumentSymbolParams, DocumentSymbolResponse,
-    FoldingRangeParams, GotoDefinitionParams, Location, ReferenceParams, RenameParams,
-    SemanticTokenModifier, SemanticTokenType, SemanticTokensFullOptions, SemanticTokensLegend,
-    SemanticTokensOptions, SemanticTokensParams, SemanticTokensServerCapabilities,
+    self as lsp_types, CodeActionParams, CodeActionProviderCapability, CodeLensParams,
+    ColorPresentationParams, ColorProviderCapability, CompletionOptions, CompletionParams,
+    DeclarationCapability, DocumentColorParams, DocumentHighlightParams, DocumentLinkOptions,
+    DocumentLinkParams, DocumentSymbolParams, DocumentSymbolResponse, FoldingRangeParams,
+    GotoDefinitionParams, LinkedEditingRangeParams, LinkedEditingRangeServerCapabilities, Location,
+    ReferenceParams, RenameParams, SelectionRangeParams, SelectionRangeProviderCapability,
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensDelta,
+    SemanticTokensDeltaParams, SemanticTokensFullDeltaResult, SemanticTokensFullOptions,
+    SemanticTokensLegend, SemanticTokensOptions, SemanticTokensParams, SemanticTokensRangeParams,
+    SemanticTokensRangeResult, SemanticTokensResult, SemanticTokensServerCapabilities,
     TextDocumentPositionParams, WorkspaceSymbolParams, WorkspaceSymbolResponse,
     request::{
-        DocumentSymbolRequest, FoldingRangeRequest, GotoDeclaration, References,
-        SemanticTokensFullRequest, WorkspaceSymbolRequest,
+        CodeActionRequest, CodeLensRequest, ColorPresentationRequest, Completion, DocumentColor,
+        DocumentHighlightRequest, DocumentLinkRequest, DocumentSymbolRequest, FoldingRangeRequest,
+        GotoDeclaration, HoverRequest, InlayHintRequest, LinkedEditingRange, References,
+        SelectionRangeRequest, SemanticTokensFullDeltaRequest, SemanticTokensFullRequest,
+        SemanticTokensRangeRequest, SignatureHelpRequest, WorkspaceSymbolRequest,
     },
 };
 use lsp_server::{Connection, Message, Response};
 use lsp_types::{
-    ApplyWorkspaceEditParams, DidChangeWatchedFilesRegistrationOptions, DocumentChanges,
-    DocumentFormattingParams, FileSystemWatcher, GlobPattern, OneOf,
+    ApplyWorkspaceEditParams, CodeLensOptions, DidChangeWatchedFilesRegistrationOptions,
+    DocumentChanges, DocumentFormattingParams, DocumentOnTypeFormattingOptions,
+    DocumentOnTypeFormattingParams, DocumentRangeFormattingParams, ExecuteCommandOptions,
+    ExecuteCommandParams, FileSystemWatcher, GlobPattern, MessageType, OneOf,
     OptionalVersionedTextDocumentIdentifier, PositionEncodingKind, PublishDiagnosticsParams,
-    Registration, RegistrationParams, RenameOptions, ServerCapabilities,
+    Registration, RegistrationParams, RenameOptions, ServerCapabilities, ShowMessageParams,
     TextDocumentContentChangeEvent, TextDocumentEdit, TextDocumentSyncCapability,
     TextDocumentSyncKind, TextDocumentSyncOptions, TextEdit, VersionedTextDocumentIdentifier,
     WorkDoneProgressOptions, WorkspaceEdit,
-    notification::DidSaveTextDocument,
     notification::{
         DidChangeTextDocument, DidChangeWatchedFiles, DidCloseTextDocument, DidOpenTextDocument,
         Notification, PublishDiagnostics,
     },
-    request::{ApplyWorkspaceEdit, Formatting, PrepareRenameRequest, Rename},
+    notification::{DidSaveTextDocument, ShowMessage},
+    request::{
+        ApplyWorkspaceEdit, ExecuteCommand, Formatting, OnTypeFormatting, PrepareRenameRequest,
+        RangeFormatting, Rename,
+    },
     request::{RegisterCapability, Request},
 };
 use miette::Diagnostic;
@@ -44,10 +62,19 @@ use ropey::Rope;
 use tree_sitter::{Query, QueryCursor};
 use tree_sitter_grz::NodeKind;
 
+mod code_actions;
+mod code_lens;
+mod colors;
+mod completion;
 mod folding_range;
 mod formatter;
+mod hover;
+mod inlay_hints;
+mod links;
 mod rename;
+mod selection_range;
 mod semantic_tokens;
+mod signature_help;
 mod symbols;
 
 pub struct GrzLsp {
@@ -57,6 +84,13 @@ pub struct GrzLsp {
     shared_data: AppHandle,
     owner_receiver: Receiver<FileOwnerMessage>,
     query_cursor: QueryCursor,
+    /// Only used for `semanticTokens/range`, so that the byte
+    /// range it sets doesn't apply to the other queries
+    range_query_cursor: QueryCursor,
+    /// The last semantic tokens sent for each file, and
+    /// their result ID, so that deltas can be sent instead
+    semantic_tokens: HashMap<Url, (String, Vec<SemanticToken>)>,
+    semantic_tokens_result_id: u64,
     // Queries
     rename_query: Query,
     semantic_tokens_query: Query,
@@ -92,6 +126,9 @@ impl GrzLsp {
             shared_data,
             owner_receiver,
             query_cursor: QueryCursor::new(),
+            range_query_cursor: QueryCursor::new(),
+            semantic_tokens: HashMap::new(),
+            semantic_tokens_result_id: 0,
             rename_query,
             semantic_tokens_query,
             folding_range_query,
@@ -137,6 +174,11 @@ impl GrzLsp {
                                     )
                                 })
                             })
+                            .chain(
+                                semantic_tokens::DJOT_TOKEN_TYPES
+                                    .iter()
+                                    .map(|name| SemanticTokenType::new(name)),
+                            )
                             .collect(),
                         token_modifiers: self
                             .semantic_tokens_query
@@ -150,17 +192,59 @@ impl GrzLsp {
                             })
                             .collect(),
                     },
-                    range: Some(false),
-                    full: Some(SemanticTokensFullOptions::Delta { delta: Some(false) }),
+                    range: Some(true),
+                    full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
                     ..Default::default()
                 }),
             ),
             declaration_provider: Some(DeclarationCapability::Simple(true)),
             references_provider: Some(OneOf::Left(true)),
+            document_highlight_provider: Some(OneOf::Left(true)),
+            selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+            linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(true)),
             document_symbol_provider: Some(OneOf::Left(true)),
             workspace_symbol_provider: Some(OneOf::Left(true)),
             document_formatting_provider: Some(OneOf::Left(true)),
+            document_range_formatting_provider: Some(OneOf::Left(true)),
+            document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                first_trigger_character: ",".to_string(),
+                more_trigger_character: Some(
+                    ["]", "}", ")"].into_iter().map(String::from).collect(),
+                ),
+            }),
             folding_range_provider: Some(lsp_types::FoldingRangeProviderCapability::Simple(true)),
+            completion_provider: Some(CompletionOptions {
+                trigger_characters: Some(
+                    ["(", "[", "{", "<", "."]
+                        .into_iter()
+                        .map(String::from)
+                        .collect(),
+                ),
+                ..Default::default()
+            }),
+            hover_provider: Some(HoverProviderCapability::Simple(true)),
+            color_provider: Some(ColorProviderCapability::Simple(true)),
+            code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+            inlay_hint_provider: Some(OneOf::Left(true)),
+            code_lens_provider: Some(CodeLensOptions {
+                resolve_provider: Some(false),
+            }),
+            execute_command_provider: Some(ExecuteCommandOptions {
+                commands: vec![
+                    code_lens::PRESENT_COMMAND.to_string(),
+                    code_lens::EXPORT_COMMAND.to_string(),
+                ],
+                work_done_progress_options: Default::default(),
+            }),
+            signature_help_provider: Some(SignatureHelpOptions {
+                trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                retrigger_characters: None,
+                work_done_progress_options: Default::default(),
+            }),
+            document_link_provider: Some(DocumentLinkOptions {
+                resolve_provider: Some(false),
+                work_done_progress_options: Default::default(),
+            }),
             position_encoding: Some(PositionEncodingKind::UTF16),
             ..Default::default()
         })
@@ -222,6 +306,172 @@ impl GrzLsp {
                 }
 
                 match req.method.as_str() {
+                    Completion::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<CompletionParams>(Completion::METHOD)
+                        {
+                            let doc = self
+                                .grz_files
+                                .get(&params.text_document_position.text_document.uri)
+                                .unwrap();
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(
+                                    rqid,
+                                    completion::completion(
+                                        params,
+                                        &doc.source,
+                                        &doc.tree,
+                                        &doc.slideshow,
+                                    ),
+                                )))
+                                .unwrap();
+                        }
+                    }
+                    HoverRequest::METHOD => {
+                        if let Ok((rqid, params)) = req.extract::<HoverParams>(HoverRequest::METHOD)
+                        {
+                            let doc = self
+                                .grz_files
+                                .get(&params.text_document_position_params.text_document.uri)
+                                .unwrap();
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(
+                                    rqid,
+                                    hover::hover(params, &doc.source, &doc.tree, &doc.slideshow),
+                                )))
+                                .unwrap();
+                        }
+                    }
+                    DocumentColor::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<DocumentColorParams>(DocumentColor::METHOD)
+                        {
+                            let doc = self.grz_files.get(&params.text_document.uri).unwrap();
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(
+                                    rqid,
+                                    colors::document_colors(&doc.source, &doc.tree),
+                                )))
+                                .unwrap();
+                        }
+                    }
+                    ColorPresentationRequest::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<ColorPresentationParams>(ColorPresentationRequest::METHOD)
+                        {
+                            let doc = self.grz_files.get(&params.text_document.uri).unwrap();
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(
+                                    rqid,
+                                    colors::color_presentations(params, &doc.source, &doc.tree),
+                                )))
+                                .unwrap();
+                        }
+                    }
+                    CodeActionRequest::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<CodeActionParams>(CodeActionRequest::METHOD)
+                        {
+                            let doc = self.grz_files.get(&params.text_document.uri).unwrap();
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(
+                                    rqid,
+                                    code_actions::code_actions(params, &doc.source, &doc.tree),
+                                )))
+                                .unwrap();
+                        }
+                    }
+                    CodeLensRequest::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<CodeLensParams>(CodeLensRequest::METHOD)
+                        {
+                            let doc = self.grz_files.get(&params.text_document.uri).unwrap();
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(
+                                    rqid,
+                                    code_lens::code_lenses(
+                                        &doc.source,
+                                        &params.text_document.uri,
+                                        &doc.tree,
+                                    ),
+                                )))
+                                .unwrap();
+                        }
+                    }
+                    ExecuteCommand::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<ExecuteCommandParams>(ExecuteCommand::METHOD)
+                        {
+                            self.execute_command(connection, params);
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(
+                                    rqid,
+                                    serde_json::Value::Null,
+                                )))
+                                .unwrap();
+                        }
+                    }
+                    SignatureHelpRequest::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<SignatureHelpParams>(SignatureHelpRequest::METHOD)
+                        {
+                            let doc = self
+                                .grz_files
+                                .get(&params.text_document_position_params.text_document.uri)
+                                .unwrap();
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(
+                                    rqid,
+                                    signature_help::signature_help(params, &doc.source, &doc.tree),
+                                )))
+                                .unwrap();
+                        }
+                    }
+                    InlayHintRequest::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<InlayHintParams>(InlayHintRequest::METHOD)
+                        {
+                            let doc = self.grz_files.get(&params.text_document.uri).unwrap();
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(
+                                    rqid,
+                                    inlay_hints::inlay_hints(
+                                        params,
+                                        &doc.source,
+                                        &doc.tree,
+                                        &doc.slideshow,
+                                    ),
+                                )))
+                                .unwrap();
+                        }
+                    }
+                    DocumentLinkRequest::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<DocumentLinkParams>(DocumentLinkRequest::METHOD)
+                        {
+                            let doc = self.grz_files.get(&params.text_document.uri).unwrap();
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(
+                                    rqid,
+                                    links::document_links(
+                                        &doc.source,
+                                        &params.text_document.uri,
+                                        &doc.tree,
+                                    ),
+                                )))
+                                .unwrap();
+                        }
+                    }
                     FoldingRangeRequest::METHOD => {
                         if let Ok((rqid, params)) =
                             req.extract::<FoldingRangeParams>(FoldingRangeRequest::METHOD)
@@ -257,6 +507,59 @@ impl GrzLsp {
                                 .unwrap();
                         }
                     }
+                    RangeFormatting::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<DocumentRangeFormattingParams>(RangeFormatting::METHOD)
+                        {
+                            let doc = self.grz_files.get(&params.text_document.uri).unwrap();
+                            let edits: Option<Vec<TextEdit>> = doc.tree.as_ref().and_then(|tree| {
+                                formatter::format_range(
+                                    &doc.source,
+                                    tree,
+                                    formatter::byte_range_from_char_range(
+                                        params.range,
+                                        &doc.source,
+                                    )
+                                    .ok()?,
+                                )
+                                .ok()
+                            });
+
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(rqid, edits)))
+                                .unwrap();
+                        }
+                    }
+                    OnTypeFormatting::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<DocumentOnTypeFormattingParams>(OnTypeFormatting::METHOD)
+                        {
+                            let position = params.text_document_position;
+                            let doc = self.grz_files.get(&position.text_document.uri).unwrap();
+                            // Only the definition the character was typed in gets formatted
+                            let edits: Option<Vec<TextEdit>> = doc.tree.as_ref().and_then(|tree| {
+                                formatter::format_range(
+                                    &doc.source,
+                                    tree,
+                                    formatter::byte_range_from_char_range(
+                                        lsp_types::Range {
+                                            start: position.position,
+                                            end: position.position,
+                                        },
+                                        &doc.source,
+                                    )
+                                    .ok()?,
+                                )
+                                .ok()
+                            });
+
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(rqid, edits)))
+                                .unwrap();
+                        }
+                    }
                     PrepareRenameRequest::METHOD => {
                         if let Ok((rqid, pos)) =
                             req.extract::<TextDocumentPositionParams>(PrepareRenameRequest::METHOD)
@@ -369,6 +672,7 @@ impl GrzLsp {
                                         goto_params,
                                         &mut self.query_cursor,
                                         &doc.tree,
+                                        &doc.includes,
                                     ),
                                 )))
                                 .unwrap();
@@ -397,18 +701,22 @@ impl GrzLsp {
                                 .unwrap();
                         }
                     }
-                    SemanticTokensFullRequest::METHOD => {
+                    DocumentHighlightRequest::METHOD => {
                         if let Ok((rqid, params)) =
-                            req.extract::<SemanticTokensParams>(SemanticTokensFullRequest::METHOD)
+                            req.extract::<DocumentHighlightParams>(DocumentHighlightRequest::METHOD)
                         {
-                            let doc = self.grz_files.get(&params.text_document.uri).unwrap();
+                            let doc = self
+                                .grz_files
+                                .get(&params.text_document_position_params.text_document.uri)
+                                .unwrap();
                             connection
                                 .sender
                                 .send(Message::Response(Response::new_ok(
                                     rqid,
-                                    semantic_tokens::semantic_tokens(
-                                        &self.semantic_tokens_query,
+                                    symbols::document_highlights(
+                                        &self.rename_query,
                                         &doc.source,
+                                        params,
                                         &mut self.query_cursor,
                                         &doc.tree,
                                     ),
@@ -416,6 +724,145 @@ impl GrzLsp {
                                 .unwrap();
                         }
                     }
+                    LinkedEditingRange::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<LinkedEditingRangeParams>(LinkedEditingRange::METHOD)
+                        {
+                            let doc = self
+                                .grz_files
+                                .get(&params.text_document_position_params.text_document.uri)
+                                .unwrap();
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(
+                                    rqid,
+                                    symbols::linked_editing_ranges(
+                                        &self.rename_query,
+                                        &doc.source,
+                                        params,
+                                        &mut self.query_cursor,
+                                        &doc.tree,
+                                    ),
+                                )))
+                                .unwrap();
+                        }
+                    }
+                    SelectionRangeRequest::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<SelectionRangeParams>(SelectionRangeRequest::METHOD)
+                        {
+                            let doc = self.grz_files.get(&params.text_document.uri).unwrap();
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(
+                                    rqid,
+                                    selection_range::selection_ranges(
+                                        params,
+                                        &doc.source,
+                                        &doc.tree,
+                                    ),
+                                )))
+                                .unwrap();
+                        }
+                    }
+                    SemanticTokensFullRequest::METHOD => {
+                        if let Ok((rqid, params)) =
+                            req.extract::<SemanticTokensParams>(SemanticTokensFullRequest::METHOD)
+                        {
+                            let doc = self.grz_files.get(&params.text_document.uri).unwrap();
+                            let result = semantic_tokens::semantic_tokens(
+                                &self.semantic_tokens_query,
+                                &doc.source,
+                                &mut self.query_cursor,
+                                &doc.tree,
+                                None,
+                            )
+                            .map(|data| {
+                                let result_id = self
+                                    .cache_semantic_tokens(params.text_document.uri, data.clone());
+                                SemanticTokensResult::Tokens(SemanticTokens {
+                                    result_id: Some(result_id),
+                                    data,
+                                })
+                            });
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(rqid, result)))
+                                .unwrap();
+                        }
+                    }
+                    SemanticTokensFullDeltaRequest::METHOD => {
+                        if let Ok((rqid, params)) = req.extract::<SemanticTokensDeltaParams>(
+                            SemanticTokensFullDeltaRequest::METHOD,
+                        ) {
+                            let doc = self.grz_files.get(&params.text_document.uri).unwrap();
+                            let result = semantic_tokens::semantic_tokens(
+                                &self.semantic_tokens_query,
+                                &doc.source,
+                                &mut self.query_cursor,
+                                &doc.tree,
+                                None,
+                            )
+                            .map(|data| {
+                                let edits = self
+                                    .semantic_tokens
+                                    .get(&params.text_document.uri)
+                                    .filter(|(result_id, _)| {
+                                        *result_id == params.previous_result_id
+                                    })
+                                    .map(|(_, old)| {
+                                        semantic_tokens::semantic_tokens_edits(old, &data)
+                                    });
+                                let result_id = self
+                                    .cache_semantic_tokens(params.text_document.uri, data.clone());
+                                match edits {
+                                    Some(edits) => SemanticTokensFullDeltaResult::TokensDelta(
+                                        SemanticTokensDelta {
+                                            result_id: Some(result_id),
+                                            edits,
+                                        },
+                                    ),
+                                    None => SemanticTokensFullDeltaResult::Tokens(SemanticTokens {
+                                        result_id: Some(result_id),
+                                        data,
+                                    }),
+                                }
+                            });
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(rqid, result)))
+                                .unwrap();
+                        }
+                    }
+                    SemanticTokensRangeRequest::METHOD => {
+                        if let Ok((rqid, params)) = req.extract::<SemanticTokensRangeParams>(
+                            SemanticTokensRangeRequest::METHOD,
+                        ) {
+                            let doc = self.grz_files.get(&params.text_document.uri).unwrap();
+                            let result =
+                                formatter::byte_range_from_char_range(params.range, &doc.source)
+                                    .ok()
+                                    .and_then(|range| {
+                                        semantic_tokens::semantic_tokens(
+                                            &self.semantic_tokens_query,
+                                            &doc.source,
+                                            &mut self.range_query_cursor,
+                                            &doc.tree,
+                                            Some(range.start_byte..range.end_byte),
+                                        )
+                                    })
+                                    .map(|data| {
+                                        SemanticTokensRangeResult::Tokens(SemanticTokens {
+                                            result_id: None,
+                                            data,
+                                        })
+                                    });
+                            connection
+                                .sender
+                                .send(Message::Response(Response::new_ok(rqid, result)))
+                                .unwrap();
+                        }
+                    }
                     Rename::METHOD => {
                         if let Ok((rqid, rename_params)) =
                             req.extract::<RenameParams>(Rename::METHOD)
@@ -477,16 +924,19 @@ impl GrzLsp {
 
                     if let Some(parse_result) = parse_result.as_ref().ok().map(|pr| Arc::clone(pr))
                     {
+                        self.report_included_errors(connection, &parse_result);
                         Self::report_error_messages(
                             connection,
                             Arc::clone(&parse_result),
                             doc.text_document.uri.clone(),
-                            new_file.version,
+                            Some(new_file.version),
                         );
                     }
 
                     self.last_edited_uri = doc.text_document.uri.clone();
-                    self.grz_files.insert(doc.text_document.uri, new_file);
+                    self.grz_files
+                        .insert(doc.text_document.uri.clone(), new_file);
+                    self.reparse_dependents(connection, &doc.text_document.uri);
 
                     if !parse_result.ok().map(|pr| pr.has_errors()).unwrap_or(true) {
                         self.handle_file_owner_message(FileOwnerMessage::Index {
@@ -500,6 +950,7 @@ impl GrzLsp {
                         serde_json::from_value(not.params).unwrap();
 
                     self.grz_files.remove(&doc.text_document.uri);
+                    self.semantic_tokens.remove(&doc.text_document.uri);
                 }
                 DidSaveTextDocument::METHOD | DidChangeWatchedFiles::METHOD => {
                     let saved_doc_uri;
@@ -526,30 +977,34 @@ impl GrzLsp {
                     }
 
                     self.shared_data.egui_ctx.forget_all_images();
-                    let doc = self.grz_files.get_mut(&saved_doc_uri).unwrap();
-                    doc.clear_incremental_state();
+                    // Included files don't have to be open
+                    if let Some(doc) = self.grz_files.get_mut(&saved_doc_uri) {
+                        doc.clear_incremental_state();
 
-                    if let Some(text) = text {
-                        doc.source = text;
-                    }
+                        if let Some(text) = text {
+                            doc.source = text;
+                        }
 
-                    let version = doc.version;
-                    self.last_edited_uri = saved_doc_uri.clone();
-                    if let Ok(parse_result) = doc.parse(&[]) {
-                        Self::report_error_messages(
-                            connection,
-                            Arc::clone(&parse_result),
-                            saved_doc_uri,
-                            version,
-                        );
+                        let version = doc.version;
+                        self.last_edited_uri = saved_doc_uri.clone();
+                        if let Ok(parse_result) = doc.parse(&[]) {
+                            self.report_included_errors(connection, &parse_result);
+                            Self::report_error_messages(
+                                connection,
+                                Arc::clone(&parse_result),
+                                saved_doc_uri.clone(),
+                                Some(version),
+                            );
 
-                        if !parse_result.has_errors() {
-                            self.handle_file_owner_message(FileOwnerMessage::Index {
-                                index: self.slide_index,
-                                reset_time: true,
-                            });
+                            if !parse_result.has_errors() {
+                                self.handle_file_owner_message(FileOwnerMessage::Index {
+                                    index: self.slide_index,
+                                    reset_time: true,
+                                });
+                            }
                         }
                     }
+                    self.reparse_dependents(connection, &saved_doc_uri);
                 }
                 DidChangeTextDocument::METHOD => {
                     let changes: lsp_types::DidChangeTextDocumentParams =
@@ -558,6 +1013,7 @@ impl GrzLsp {
                     let doc = self.grz_files.get_mut(&changes.text_document.uri).unwrap();
 
                     let mut has_errors = false;
+                    let mut last_parse_result = None;
                     if doc.version < changes.text_document.version {
                         doc.version = changes.text_document.version;
 
@@ -644,7 +1100,7 @@ impl GrzLsp {
                                     connection,
                                     Arc::clone(&parse_result),
                                     changes.text_document.uri.clone(),
-                                    changes.text_document.version,
+                                    Some(changes.text_document.version),
                                 );
 
                                 self.last_edited_uri = changes.text_document.uri.clone();
@@ -656,9 +1112,14 @@ impl GrzLsp {
                                 if parse_result.has_errors() {
                                     has_errors = true;
                                 }
+                                last_parse_result = Some(parse_result);
                             }
                         }
                     }
+                    if let Some(parse_result) = last_parse_result {
+                        self.report_included_errors(connection, &parse_result);
+                        self.reparse_dependents(connection, &changes.text_document.uri);
+                    }
                     if !has_errors {
                         self.handle_file_owner_message(FileOwnerMessage::Index {
                             index: self.slide_index,
@@ -673,11 +1134,127 @@ impl GrzLsp {
         false
     }
 
+    fn cache_semantic_tokens(&mut self, uri: Url, tokens: Vec<SemanticToken>) -> String {
+        self.semantic_tokens_result_id += 1;
+        let result_id = self.semantic_tokens_result_id.to_string();
+        self.semantic_tokens
+            .insert(uri, (result_id.clone(), tokens));
+        result_id
+    }
+
+    fn execute_command(&mut self, connection: &Connection, params: ExecuteCommandParams) {
+        let mut arguments = params.arguments.into_iter();
+        let (Some(Ok(uri)), Some(Ok(index))) = (
+            arguments.next().map(serde_json::from_value::<Url>),
+            arguments.next().map(serde_json::from_value::<usize>),
+        ) else {
+            return;
+        };
+
+        match params.command.as_str() {
+            code_lens::PRESENT_COMMAND => {
+                self.last_edited_uri = uri;
+                self.handle_file_owner_message(FileOwnerMessage::Index {
+                    index,
+                    reset_time: true,
+                });
+            }
+            code_lens::EXPORT_COMMAND => {
+                let Some(doc) = self.grz_files.get(&uri) else {
+                    return;
+                };
+                let Ok(path) = uri.to_file_path() else {
+                    return;
+                };
+                // `deck.grz` exports its first slide to `deck-1.png`
+                let out_path = path.with_file_name(format!(
+                    "{}-{}.png",
+                    path.file_stem()
+                        .map(|s| s.to_string_lossy())
+                        .unwrap_or_default(),
+                    index + 1
+                ));
+                let (typ, message) = match GrzExporter::new(
+                    &doc.slideshow,
+                    Arc::clone(&self.shared_data.font_system),
+                )
+                .export(&out_path, BASE_SIZE.size(), index..index + 1)
+                {
+                    Ok(()) => (
+                        MessageType::INFO,
+                        format!("Exported slide {} to {}", index + 1, out_path.display()),
+                    ),
+                    Err(e) => (
+                        MessageType::ERROR,
+                        format!("Failed to export slide {}: {}", index + 1, e),
+                    ),
+                };
+                connection
+                    .sender
+                    .send(Message::Notification(lsp_server::Notification::new(
+                        ShowMessage::METHOD.to_string(),
+                        ShowMessageParams { typ, message },
+                    )))
+                    .unwrap();
+            }
+            _ => {}
+        }
+    }
+
+    /// Re-parses every open file that includes the file at
+    /// `uri`, using its text from the editor if it's open
+    fn reparse_dependents(&mut self, connection: &Connection, uri: &Url) {
+        let Ok(path) = uri.to_file_path() else {
+            return;
+        };
+        let path = include::canonical_path(&path);
+        let source = self.grz_files.get(uri).map(|doc| doc.source.clone());
+
+        let mut reparsed = Vec::new();
+        for (dependent_uri, doc) in self.grz_files.iter_mut() {
+            if dependent_uri != uri && doc.reload_include(&path, source.as_ref()) {
+                doc.clear_incremental_state();
+                if let Ok(parse_result) = doc.parse(&[]) {
+                    reparsed.push((dependent_uri.clone(), parse_result, doc.version));
+                }
+            }
+        }
+
+        let mut presenting_changed = false;
+        for (dependent_uri, parse_result, version) in reparsed {
+            self.report_included_errors(connection, &parse_result);
+            if dependent_uri == self.last_edited_uri && !parse_result.has_errors() {
+                presenting_changed = true;
+            }
+            Self::report_error_messages(connection, parse_result, dependent_uri, Some(version));
+        }
+        if presenting_changed {
+            self.handle_file_owner_message(FileOwnerMessage::Index {
+                index: self.slide_index,
+                reset_time: false,
+            });
+        }
+    }
+
+    /// Publishes the errors of included files that aren't
+    /// open, since open files report their own errors
+    fn report_included_errors(&self, connection: &Connection, errors: &ErrsWithSource) {
+        for (_, (path, included_errors)) in &errors.included {
+            let Ok(uri) = Url::from_file_path(path) else {
+                continue;
+            };
+            if !self.grz_files.contains_key(&uri) {
+                Self::report_error_messages(connection, Arc::clone(included_errors), uri, None);
+            }
+            self.report_included_errors(connection, included_errors);
+        }
+    }
+
     fn report_error_messages(
         connection: &Connection,
         errors: Arc<ErrsWithSource>,
         uri: Url,
-        version: i32,
+        version: Option<i32>,
     ) {
         let mut diagnostics = Vec::with_capacity(errors.errors.count());
         for (error, label) in errors
@@ -709,6 +1286,9 @@ impl GrzLsp {
                             })
                             .unwrap_or(lsp_types::DiagnosticSeverity::ERROR),
                     ),
+                    code: error
+                        .code()
+                        .map(|code| lsp_types::NumberOrString::String(code.to_string())),
                     source: Some("Grezi LSP".to_owned()),
                     message: format!("{}", label.label().unwrap_or("Error here (unknown)")),
                     ..Default::default()
@@ -722,7 +1302,7 @@ impl GrzLsp {
                 PublishDiagnosticsParams {
                     uri,
                     diagnostics,
-                    version: Some(version),
+                    version,
                 },
             )))
             .unwrap();
@@ -756,6 +1336,19 @@ impl GrzLsp {
                 self.slide_index = self.slide_index.saturating_sub(1);
                 reset_time = false;
             }
+            FileOwnerMessage::Link(link) => {
+                match self
+                    .grz_files
+                    .get(&self.last_edited_uri)
+                    .and_then(|grz_file| grz_file.slideshow.slide_index(&link))
+                {
+                    Some(index) => {
+                        self.slide_index = index;
+                        reset_time = true;
+                    }
+                    None => return,
+                }
+            }
             FileOwnerMessage::ResetFile => {
                 if let Some(grz_file) = self.grz_files.get_mut(&self.last_edited_uri) {
                     let parse_result = grz_file.update_file().unwrap();
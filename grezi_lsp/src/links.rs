@@ -0,0 +1,112 @@
+use std::{borrow::Cow, sync::Arc};
+
+use grezi_parser::parse::{cursor::GrzCursor, error::ErrsWithSource};
+use helix_lsp_types as lsp_types;
+use lsp_types::{DocumentLink, Url};
+use ropey::Rope;
+use tree_sitter::{Node, Tree};
+use tree_sitter_grz::{FieldName, NodeKind};
+
+use super::formatter::char_range_from_byte_range;
+
+/// Makes the `value` of every `Image` object a link to the
+/// image it resolves to, and every `INCLUDE` and `THEME`
+/// register a link to the file it loads
+pub fn document_links(
+    current_rope: &Rope,
+    currently_open: &Url,
+    tree: &Option<Tree>,
+) -> Option<Vec<DocumentLink>> {
+    let tree = tree.as_ref()?;
+    let mut links = Vec::new();
+
+    let mut cursor = tree.walk();
+    for definition in tree.root_node().named_children(&mut cursor) {
+        if NodeKind::from(definition.kind_id()) == NodeKind::SymRegister {
+            let mut cursor = definition.walk();
+            if let Some(link) = definition
+                .named_children(&mut cursor)
+                .find(|n| NodeKind::from(n.kind_id()) == NodeKind::SymObjParam)
+                .filter(|param| {
+                    param
+                        .child_by_field_id(FieldName::FieldKey as u16)
+                        .map(|key| {
+                            let key = current_rope.byte_slice(key.byte_range());
+                            key == "INCLUDE" || key == "THEME"
+                        })
+                        .unwrap_or_default()
+                })
+                .and_then(|param| param.child_by_field_id(FieldName::FieldValue as u16))
+                .and_then(|value| path_link(value, tree, current_rope, currently_open))
+            {
+                links.push(link);
+            }
+            continue;
+        }
+        if NodeKind::from(definition.kind_id()) != NodeKind::SymObj {
+            continue;
+        }
+        let mut cursor = definition.walk();
+        let Some(obj_inner) = definition
+            .named_children(&mut cursor)
+            .find(|n| NodeKind::from(n.kind_id()) == NodeKind::SymObjInner)
+        else {
+            continue;
+        };
+        if !obj_inner
+            .child_by_field_id(FieldName::FieldTy as u16)
+            .map(|ty| current_rope.byte_slice(ty.byte_range()) == "Image")
+            .unwrap_or_default()
+        {
+            continue;
+        }
+
+        let mut cursor = obj_inner.walk();
+        for param in obj_inner
+            .named_children(&mut cursor)
+            .filter(|n| NodeKind::from(n.kind_id()) == NodeKind::SymObjParam)
+        {
+            let is_value = param
+                .child_by_field_id(FieldName::FieldKey as u16)
+                .map(|key| current_rope.byte_slice(key.byte_range()) == "value")
+                .unwrap_or(true);
+            if !is_value {
+                continue;
+            }
+            if let Some(link) = param
+                .child_by_field_id(FieldName::FieldValue as u16)
+                .and_then(|value| path_link(value, tree, current_rope, currently_open))
+            {
+                links.push(link);
+            }
+        }
+    }
+
+    Some(links)
+}
+
+fn path_link(
+    value: Node,
+    tree: &Tree,
+    current_rope: &Rope,
+    currently_open: &Url,
+) -> Option<DocumentLink> {
+    let mut cursor = GrzCursor::from_node(
+        value,
+        tree,
+        current_rope,
+        Arc::new(ErrsWithSource::default()),
+    );
+    let path: Cow<'_, str> = cursor.node_to_string_literal().ok()?.into();
+
+    // Paths are resolved relative to the GRZ file, just
+    // like `ObjInner::parse` and `GrzRoot::include` do
+    let target = currently_open.join(path.as_ref()).ok()?;
+
+    Some(DocumentLink {
+        range: char_range_from_byte_range(value.range(), current_rope).ok()?,
+        tooltip: target.to_file_path().ok().map(|p| p.display().to_string()),
+        target: Some(target),
+        data: None,
+    })
+}
@@ -0,0 +1,56 @@
+use grezi_parser::parse::byte_pos_from_char_pos;
+use helix_lsp_types as lsp_types;
+use lsp_types::{SelectionRange, SelectionRangeParams};
+use ropey::Rope;
+use tree_sitter::Tree;
+use tree_sitter_grz::NodeKind;
+
+use super::formatter::char_range_from_byte_range;
+
+/// Expands the selection through the named nodes above each
+/// position, such as a parameter value, its `obj_param`, the
+/// `obj_inner` and finally the whole object
+pub fn selection_ranges(
+    params: SelectionRangeParams,
+    current_rope: &Rope,
+    tree: &Option<Tree>,
+) -> Option<Vec<SelectionRange>> {
+    let tree = tree.as_ref()?;
+
+    params
+        .positions
+        .into_iter()
+        .map(|position| {
+            let point = byte_pos_from_char_pos(
+                (position.line as usize, position.character as usize),
+                current_rope,
+            )
+            .ok()?;
+            let mut node = tree
+                .root_node()
+                .named_descendant_for_point_range(point, point)?;
+
+            let mut ancestors = vec![node];
+            while let Some(parent) = node.parent() {
+                node = parent;
+                if NodeKind::from(node.kind_id()) == NodeKind::SymSourceFile {
+                    break;
+                }
+                // Nodes that span the same text as their
+                // child would make expanding do nothing
+                if node.byte_range() != ancestors.last()?.byte_range() {
+                    ancestors.push(node);
+                }
+            }
+
+            let mut selection: Option<SelectionRange> = None;
+            for ancestor in ancestors.into_iter().rev() {
+                selection = Some(SelectionRange {
+                    range: char_range_from_byte_range(ancestor.range(), current_rope).ok()?,
+                    parent: selection.map(Box::new),
+                });
+            }
+            selection
+        })
+        .collect()
+}
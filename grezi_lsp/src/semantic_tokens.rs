@@ -1,18 +1,34 @@
+use std::ops::Range;
+
 use helix_core::{
     Rope,
     syntax::RopeProvider,
-    tree_sitter::{Point, Query, QueryCursor, Tree},
+    tree_sitter::{Node, Point, Query, QueryCursor, Tree},
 };
 use helix_lsp_types as lsp_types;
-use lsp_types::{SemanticToken, SemanticTokens, SemanticTokensResult};
+use jotdown::{Container, Event};
+use lsp_types::{SemanticToken, SemanticTokensEdit};
 use tree_sitter::StreamingIterator;
+use tree_sitter_grz::{FieldName, NodeKind};
+
+/// Token types for djot markup inside of text objects. These
+/// come after the captures of the semantic tokens query in the legend
+pub const DJOT_TOKEN_TYPES: &[&str] = &["emphasis", "strong", "code", "heading"];
+
+/// A token positioned in bytes, before it's delta encoded
+struct AbsoluteToken {
+    range: Range<usize>,
+    token_type: u32,
+    modifiers: u32,
+}
 
 pub fn semantic_tokens(
     semantic_token_query: &Query,
     current_rope: &Rope,
     query_cursor: &mut QueryCursor,
     tree: &Option<Tree>,
-) -> Option<SemanticTokensResult> {
+    byte_range: Option<Range<usize>>,
+) -> Option<Vec<SemanticToken>> {
     let tree = tree.as_ref()?;
     let start_node = tree.root_node();
 
@@ -22,75 +38,270 @@ pub fn semantic_tokens(
             column: usize::MAX,
         },
     );
+    query_cursor.set_byte_range(byte_range.unwrap_or(0..usize::MAX));
     let mut iter = query_cursor.matches(
         semantic_token_query,
         start_node,
         RopeProvider(current_rope.slice(..)),
     );
 
-    let mut tokens = Vec::new();
-    let mut last_range = helix_core::tree_sitter::Range {
-        start_byte: 0,
-        end_byte: 0,
-        start_point: Point { row: 0, column: 0 },
-        end_point: Point { row: 0, column: 0 },
-    };
+    let mut tokens: Vec<AbsoluteToken> = Vec::new();
     while let Some(query_match) = iter.next() {
         let capture = query_match.captures.last().unwrap();
-        let range = capture.node.range();
-
-        if last_range != range {
-            let mut delta_line = (range.start_point.row - last_range.end_point.row) as u32;
-            let mut multiline = false;
-            for line in range.start_point.row..=range.end_point.row {
-                tokens.push(SemanticToken {
-                    delta_line,
-                    delta_start: if multiline {
-                        0
-                    } else if delta_line == 0 {
-                        (range.start_point.column - last_range.start_point.column) as u32
-                    } else {
-                        range.start_point.column as u32
-                    },
-                    length: if line == range.start_point.row {
-                        if (range.end_point.row - range.start_point.row) > 0 {
-                            let slice = current_rope
-                                .line(line)
-                                .get_byte_slice(range.start_point.column..);
-
-                            slice?.len_chars() as u32
-                        } else {
-                            (range.end_point.column - range.start_point.column) as u32
-                        }
-                    } else if line == range.end_point.row {
-                        let slice = current_rope
-                            .line(line)
-                            .get_byte_slice(..range.end_point.column);
+        let range = capture.node.byte_range();
 
-                        slice?.len_chars() as u32
-                    } else {
-                        current_rope.line(line).len_chars() as u32
-                    },
-                    token_type: capture.index,
-                    token_modifiers_bitset: if semantic_token_query.capture_names()
-                        [capture.index as usize]
-                        .contains('.')
-                    {
-                        0b00000001
-                    } else {
-                        0
-                    },
-                });
-                delta_line = 1;
-                multiline = true;
+        if tokens.last().map(|t| t.range == range).unwrap_or_default() {
+            continue;
+        }
+        let token = AbsoluteToken {
+            range,
+            token_type: capture.index,
+            modifiers: if semantic_token_query.capture_names()[capture.index as usize].contains('.')
+            {
+                0b00000001
+            } else {
+                0
+            },
+        };
+
+        if is_djot(capture.node, current_rope) {
+            split_djot(
+                token,
+                capture.node,
+                current_rope,
+                semantic_token_query.capture_names().len() as u32,
+                &mut tokens,
+            );
+        } else {
+            tokens.push(token);
+        }
+    }
+
+    encode(&tokens, current_rope)
+}
+
+/// Diffs two sets of tokens, producing a single edit that
+/// replaces everything between their common prefix and suffix
+pub fn semantic_tokens_edits(
+    old: &[SemanticToken],
+    new: &[SemanticToken],
+) -> Vec<SemanticTokensEdit> {
+    let prefix = old.iter().zip(new).take_while(|(o, n)| o == n).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(o, n)| o == n)
+        .count();
+
+    if prefix == old.len() && prefix == new.len() {
+        return Vec::new();
+    }
+
+    // Edits are indexed by the integers in the encoded
+    // data, and every token is 5 integers
+    vec![SemanticTokensEdit {
+        start: prefix as u32 * 5,
+        delete_count: (old.len() - prefix - suffix) as u32 * 5,
+        data: Some(new[prefix..new.len() - suffix].to_vec()),
+    }]
+}
+
+/// Whether a node is the `value` of a text object,
+/// which is formatted with djot
+fn is_djot(node: Node, current_rope: &Rope) -> bool {
+    if NodeKind::from(node.kind_id()) != NodeKind::SymStringLiteral {
+        return false;
+    }
+    let Some(param) = node
+        .parent()
+        .filter(|p| NodeKind::from(p.kind_id()) == NodeKind::SymObjParam)
+    else {
+        return false;
+    };
+    let is_value = param
+        .child_by_field_id(FieldName::FieldKey as u16)
+        .map(|key| current_rope.byte_slice(key.byte_range()) == "value")
+        .unwrap_or(true);
+
+    is_value
+        && param
+            .parent()
+            .and_then(|obj_inner| obj_inner.child_by_field_id(FieldName::FieldTy as u16))
+            .map(|ty| {
+                let ty = current_rope.byte_slice(ty.byte_range());
+                ty == "Header" || ty == "Paragraph"
+            })
+            .unwrap_or_default()
+}
+
+/// Splits a string token around the djot markup inside of it
+fn split_djot(
+    string: AbsoluteToken,
+    node: Node,
+    current_rope: &Rope,
+    first_djot_type: u32,
+    tokens: &mut Vec<AbsoluteToken>,
+) {
+    // Djot is parsed from the contents of the string with its
+    // escapes resolved, the same way the parser formats it. Each
+    // piece of the text remembers where it starts in the text
+    // and in the source
+    let mut text = String::new();
+    let mut pieces: Vec<(usize, usize)> = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        let source = match NodeKind::from(child.kind_id()) {
+            NodeKind::SymStringContent | NodeKind::SymRawStringContent => child.byte_range(),
+            NodeKind::SymEscapeSequence => child.start_byte() + 1..child.end_byte(),
+            _ => continue,
+        };
+        pieces.push((text.len(), source.start));
+        text.push_str(&current_rope.byte_slice(source).to_string());
+    }
+    let to_source = |offset: usize, end: bool| {
+        pieces
+            .iter()
+            .rev()
+            .find(|(start, _)| {
+                if end {
+                    *start < offset
+                } else {
+                    *start <= offset
+                }
+            })
+            .map(|(start, source)| source + offset - start)
+            .unwrap_or(string.range.start)
+    };
+
+    let mut markup = Vec::new();
+    let mut open = Vec::new();
+    for (event, range) in jotdown::Parser::new(&text).into_offset_iter() {
+        let token_type = |container: &Container| match container {
+            Container::Emphasis => Some(0),
+            Container::Strong => Some(1),
+            Container::Verbatim => Some(2),
+            Container::Heading { .. } => Some(3),
+            _ => None,
+        };
+        match event {
+            Event::Start(container, _) => {
+                if let Some(token_type) = token_type(&container) {
+                    open.push((token_type, range.start));
+                }
+            }
+            Event::End(container) => {
+                if let Some(token_type) = token_type(&container) {
+                    if let Some(position) = open.iter().rposition(|(t, _)| *t == token_type) {
+                        let (_, start) = open.remove(position);
+                        // Only the outermost markup gets a token,
+                        // since tokens can't overlap
+                        if open.is_empty() {
+                            markup.push((start..range.end, token_type));
+                        }
+                    }
+                }
             }
+            _ => {}
+        }
+    }
+
+    let mut position = string.range.start;
+    for (range, token_type) in markup {
+        let range = to_source(range.start, false)..to_source(range.end, true);
+        if range.start > position {
+            tokens.push(AbsoluteToken {
+                range: position..range.start,
+                ..string
+            });
+        }
+        position = range.end;
+        tokens.push(AbsoluteToken {
+            range,
+            token_type: first_djot_type + token_type,
+            modifiers: 0,
+        });
+    }
+    if position < string.range.end {
+        tokens.push(AbsoluteToken {
+            range: position..string.range.end,
+            ..string
+        });
+    }
+}
+
+/// Delta encodes tokens, splitting the ones that span multiple lines
+fn encode(tokens: &[AbsoluteToken], current_rope: &Rope) -> Option<Vec<SemanticToken>> {
+    let point = |byte: usize| -> Option<Point> {
+        let row = current_rope.try_byte_to_line(byte).ok()?;
+        Some(Point {
+            row,
+            column: byte - current_rope.try_line_to_byte(row).ok()?,
+        })
+    };
 
-            last_range = range;
+    let mut encoded = Vec::new();
+    let mut last_start = Point { row: 0, column: 0 };
+    let mut last_end = Point { row: 0, column: 0 };
+    for token in tokens {
+        let start = point(token.range.start)?;
+        let end = point(token.range.end)?;
+
+        let mut delta_line = (start.row - last_end.row) as u32;
+        let mut multiline = false;
+        for line in start.row..=end.row {
+            encoded.push(SemanticToken {
+                delta_line,
+                delta_start: if multiline {
+                    0
+                } else if delta_line == 0 {
+                    current_rope
+                        .line(line)
+                        .get_byte_slice(last_start.column..start.column)?
+                        .len_chars() as u32
+                } else {
+                    current_rope
+                        .line(line)
+                        .get_byte_slice(..start.column)?
+                        .len_chars() as u32
+                },
+                length: if line == start.row {
+                    if end.row > start.row {
+                        current_rope
+                            .line(line)
+                            .get_byte_slice(start.column..)?
+                            .len_chars() as u32
+                    } else {
+                        current_rope
+                            .line(line)
+                            .get_byte_slice(start.column..end.column)?
+                            .len_chars() as u32
+                    }
+                } else if line == end.row {
+                    current_rope
+                        .line(line)
+                        .get_byte_slice(..end.column)?
+                        .len_chars() as u32
+                } else {
+                    current_rope.line(line).len_chars() as u32
+                },
+                token_type: token.token_type,
+                token_modifiers_bitset: token.modifiers,
+            });
+            delta_line = 1;
+            multiline = true;
         }
+
+        last_start = if end.row > start.row {
+            Point {
+                row: end.row,
+                column: 0,
+            }
+        } else {
+            start
+        };
+        last_end = end;
     }
 
-    Some(SemanticTokensResult::Tokens(SemanticTokens {
-        data: tokens,
-        ..Default::default()
-    }))
+    Some(encoded)
 }
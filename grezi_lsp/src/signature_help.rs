@@ -0,0 +1,106 @@
+use grezi_parser::parse::{byte_pos_from_char_pos, slideshow::actions::ActionSignature};
+use helix_lsp_types as lsp_types;
+use lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, SignatureHelp,
+    SignatureHelpParams, SignatureInformation,
+};
+use ropey::Rope;
+use tree_sitter::{Node, Tree};
+use tree_sitter_grz::NodeKind;
+
+pub fn signature_help(
+    params: SignatureHelpParams,
+    current_rope: &Rope,
+    tree: &Option<Tree>,
+) -> Option<SignatureHelp> {
+    let tree = tree.as_ref()?;
+    let position = params.text_document_position_params.position;
+    let point = byte_pos_from_char_pos(
+        (position.line as usize, position.character as usize),
+        current_rope,
+    )
+    .ok()?;
+    let byte = current_rope.try_line_to_byte(point.row).ok()? + point.column;
+
+    // The cursor usually sits right after `(` or `,`, so look
+    // at the node before it. While an action is being typed, it
+    // might only exist inside of an `ERROR` node
+    let mut node = tree
+        .root_node()
+        .descendant_for_byte_range(byte.saturating_sub(1), byte.saturating_sub(1))?;
+    let (function, active_parameter) = loop {
+        if NodeKind::from(node.kind_id()) == NodeKind::SymSlideFunction || node.is_error() {
+            if let Some(found) = function_before(node, byte, current_rope) {
+                break found;
+            }
+        }
+        node = node.parent()?;
+    };
+
+    let signature = ActionSignature::get(&function)?;
+    let label = signature.label();
+
+    // Parameters are labeled with offsets into the signature label,
+    // so that duplicate names are highlighted correctly. Optional
+    // parameters are in brackets, which aren't highlighted
+    let mut offset = signature.name.len() as u32 + 1;
+    let parameters = signature
+        .parameters
+        .iter()
+        .enumerate()
+        .map(|(index, (name, documentation))| {
+            let brackets = if index < signature.required { 0 } else { 2 };
+            let start = offset + brackets / 2;
+            offset += name.len() as u32 + brackets + 2;
+            ParameterInformation {
+                label: ParameterLabel::LabelOffsets([start, start + name.len() as u32]),
+                documentation: Some(Documentation::String(documentation.to_string())),
+            }
+        })
+        .collect();
+
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation: Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: signature.documentation.to_string(),
+            })),
+            parameters: Some(parameters),
+            active_parameter: None,
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(active_parameter),
+    })
+}
+
+/// Finds the name of the function whose argument list the cursor
+/// is in, along with the index of the argument the cursor is on
+fn function_before(node: Node, byte: usize, current_rope: &Rope) -> Option<(String, u32)> {
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node
+        .children(&mut cursor)
+        .filter(|n| !n.is_extra() && n.start_byte() < byte)
+        .collect();
+
+    let open = children.iter().rposition(|n| n.kind() == "(")?;
+    // The argument list was already closed
+    if children[open..]
+        .iter()
+        .any(|n| n.kind() == ")" && n.end_byte() <= byte)
+    {
+        return None;
+    }
+    let name = children[..open]
+        .last()
+        .filter(|n| NodeKind::from(n.kind_id()) == NodeKind::SymIdentifier)?;
+    let active_parameter = children[open..]
+        .iter()
+        .filter(|n| n.kind() == "," && n.end_byte() <= byte)
+        .count();
+
+    Some((
+        current_rope.byte_slice(name.byte_range()).to_string(),
+        active_parameter as u32,
+    ))
+}
@@ -1,18 +1,21 @@
 use std::sync::Arc;
 
-use grezi_parser::parse::{cursor::GrzCursor, error::ErrsWithSource};
+use grezi_parser::parse::{
+    byte_pos_from_char_pos, cursor::GrzCursor, error::ErrsWithSource, slideshow::include::Includes,
+};
 use helix_core::{
     Rope,
     syntax::RopeProvider,
-    tree_sitter::{Point, Query, QueryCursor, Tree},
+    tree_sitter::{Node, Point, Query, QueryCursor, Tree},
 };
 use helix_lsp_types as lsp_types;
 use lsp_types::{
-    DocumentSymbol, GotoDefinitionParams, GotoDefinitionResponse, Location, ReferenceParams,
-    SymbolKind, Url,
+    DocumentHighlight, DocumentHighlightKind, DocumentHighlightParams, DocumentSymbol,
+    GotoDefinitionParams, GotoDefinitionResponse, LinkedEditingRangeParams, LinkedEditingRanges,
+    Location, ReferenceParams, SymbolKind, TextDocumentPositionParams, Url,
 };
 use tree_sitter::StreamingIterator;
-use tree_sitter_grz::NodeKind;
+use tree_sitter_grz::{FieldName, NodeKind};
 
 use super::formatter::char_range_from_byte_range;
 
@@ -34,6 +37,108 @@ pub fn references(
         .descendant_for_point_range(point, point)
         .unwrap();
 
+    occurrences(
+        rename_query,
+        current_rope,
+        reference_node,
+        query_cursor,
+        tree,
+    )
+    .into_iter()
+    .map(|node| {
+        Some(Location {
+            uri: references.text_document_position.text_document.uri.clone(),
+            range: char_range_from_byte_range(node.range(), current_rope).ok()?,
+        })
+    })
+    .collect()
+}
+
+/// Highlights every declaration and use of the object
+/// or viewbox name under the cursor
+pub fn document_highlights(
+    rename_query: &Query,
+    current_rope: &Rope,
+    highlight: DocumentHighlightParams,
+    query_cursor: &mut QueryCursor,
+    tree: &Option<Tree>,
+) -> Option<Vec<DocumentHighlight>> {
+    let tree = tree.as_ref()?;
+    let name_node = name_at(current_rope, highlight.text_document_position_params, tree)?;
+
+    occurrences(rename_query, current_rope, name_node, query_cursor, tree)
+        .into_iter()
+        .filter(|node| is_name(*node))
+        .map(|node| {
+            Some(DocumentHighlight {
+                range: char_range_from_byte_range(node.range(), current_rope).ok()?,
+                kind: Some(if is_declaration(node) {
+                    DocumentHighlightKind::WRITE
+                } else {
+                    DocumentHighlightKind::READ
+                }),
+            })
+        })
+        .collect()
+}
+
+/// Lets an object or viewbox be renamed in place, by
+/// editing its declaration and every use at once
+pub fn linked_editing_ranges(
+    rename_query: &Query,
+    current_rope: &Rope,
+    linked_editing: LinkedEditingRangeParams,
+    query_cursor: &mut QueryCursor,
+    tree: &Option<Tree>,
+) -> Option<LinkedEditingRanges> {
+    let tree = tree.as_ref()?;
+    let name_node = name_at(
+        current_rope,
+        linked_editing.text_document_position_params,
+        tree,
+    )?;
+
+    let ranges = occurrences(rename_query, current_rope, name_node, query_cursor, tree)
+        .into_iter()
+        .filter(|node| is_name(*node))
+        .map(|node| char_range_from_byte_range(node.range(), current_rope).ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(LinkedEditingRanges {
+        ranges,
+        word_pattern: Some(r"[\p{XID_Start}_][\p{XID_Continue}]*".to_string()),
+    })
+}
+
+/// Finds the object or viewbox name under the cursor
+fn name_at<'a>(
+    current_rope: &Rope,
+    position: TextDocumentPositionParams,
+    tree: &'a Tree,
+) -> Option<Node<'a>> {
+    let point = byte_pos_from_char_pos(
+        (
+            position.position.line as usize,
+            position.position.character as usize,
+        ),
+        current_rope,
+    )
+    .ok()?;
+
+    tree.root_node()
+        .descendant_for_point_range(point, point)
+        .filter(|node| is_name(*node))
+}
+
+/// Every identifier matched by the rename query
+/// with the same text as `node`
+fn occurrences<'a>(
+    rename_query: &Query,
+    current_rope: &Rope,
+    node: Node,
+    query_cursor: &mut QueryCursor,
+    tree: &'a Tree,
+) -> Vec<Node<'a>> {
     query_cursor.set_point_range(
         Point { row: 0, column: 0 }..Point {
             row: usize::MAX,
@@ -46,21 +151,49 @@ pub fn references(
         RopeProvider(current_rope.slice(..)),
     );
 
-    let mut locations = Vec::new();
-
+    let name = current_rope.byte_slice(node.byte_range());
+    let mut nodes = Vec::new();
     while let Some(query_match) = iter.next() {
-        if current_rope.byte_slice(query_match.captures[0].node.byte_range())
-            == current_rope.byte_slice(reference_node.byte_range())
-        {
-            let range = query_match.captures[0].node.range();
-            locations.push(Location {
-                uri: references.text_document_position.text_document.uri.clone(),
-                range: char_range_from_byte_range(range, current_rope).ok()?,
-            });
+        let capture = query_match.captures[0].node;
+        if current_rope.byte_slice(capture.byte_range()) == name {
+            nodes.push(capture);
         }
     }
 
-    Some(locations)
+    nodes
+}
+
+/// Whether an identifier names an object or a viewbox, as
+/// opposed to a parameter, an object type or an action
+fn is_name(node: Node) -> bool {
+    if NodeKind::from(node.kind_id()) != NodeKind::SymIdentifier {
+        return false;
+    }
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    let field = |field: FieldName| {
+        parent
+            .child_by_field_id(field as u16)
+            .is_some_and(|child| child.id() == node.id())
+    };
+
+    match NodeKind::from(parent.kind_id()) {
+        NodeKind::SymObj | NodeKind::SymViewbox => field(FieldName::FieldName),
+        NodeKind::SymSlideObj => field(FieldName::FieldObject),
+        NodeKind::SymVbRef => field(FieldName::FieldViewbox),
+        NodeKind::SymSlideFunction => !field(FieldName::FieldFunction),
+        _ => false,
+    }
+}
+
+fn is_declaration(node: Node) -> bool {
+    node.parent().is_some_and(|parent| {
+        matches!(
+            NodeKind::from(parent.kind_id()),
+            NodeKind::SymObj | NodeKind::SymViewbox
+        )
+    })
 }
 
 pub fn goto_declaration(
@@ -70,6 +203,7 @@ pub fn goto_declaration(
     goto_declaration: GotoDefinitionParams,
     query_cursor: &mut QueryCursor,
     tree: &Option<Tree>,
+    includes: &Includes,
 ) -> Option<GotoDefinitionResponse> {
     let tree = tree.as_ref()?;
     let point = Point {
@@ -84,7 +218,66 @@ pub fn goto_declaration(
         .root_node()
         .descendant_for_point_range(point, point)
         .unwrap();
+    let name = current_rope.byte_slice(usage_node.byte_range()).to_string();
+
+    if let Some(range) = find_declaration(
+        top_level_search_query,
+        current_rope,
+        &name,
+        query_cursor,
+        tree,
+    ) {
+        return Some(GotoDefinitionResponse::Scalar(Location {
+            uri: currently_open,
+            range,
+        }));
+    }
+
+    find_included_declaration(top_level_search_query, &name, query_cursor, includes)
+        .map(GotoDefinitionResponse::Scalar)
+}
 
+/// Looks for a declaration in the files that are included,
+/// and the files they include
+fn find_included_declaration(
+    top_level_search_query: &Query,
+    name: &str,
+    query_cursor: &mut QueryCursor,
+    includes: &Includes,
+) -> Option<Location> {
+    for (path, include) in includes.iter() {
+        let file = &include.file;
+        if let Some(range) = file.tree.as_ref().and_then(|tree| {
+            find_declaration(
+                top_level_search_query,
+                &file.source,
+                name,
+                query_cursor,
+                tree,
+            )
+        }) {
+            return Some(Location {
+                uri: Url::from_file_path(path).ok()?,
+                range,
+            });
+        }
+        if let Some(location) =
+            find_included_declaration(top_level_search_query, name, query_cursor, &file.includes)
+        {
+            return Some(location);
+        }
+    }
+
+    None
+}
+
+fn find_declaration(
+    top_level_search_query: &Query,
+    current_rope: &Rope,
+    name: &str,
+    query_cursor: &mut QueryCursor,
+    tree: &Tree,
+) -> Option<lsp_types::Range> {
     query_cursor.set_point_range(
         Point { row: 0, column: 0 }..Point {
             row: usize::MAX,
@@ -98,14 +291,9 @@ pub fn goto_declaration(
     );
 
     while let Some(query_match) = iter.next() {
-        if current_rope.byte_slice(query_match.captures[1].node.byte_range())
-            == current_rope.byte_slice(usage_node.byte_range())
-        {
+        if current_rope.byte_slice(query_match.captures[1].node.byte_range()) == name {
             let range = query_match.captures[1].node.range();
-            return Some(GotoDefinitionResponse::Scalar(Location {
-                uri: currently_open,
-                range: char_range_from_byte_range(range, current_rope).ok()?,
-            }));
+            return char_range_from_byte_range(range, current_rope).ok();
         }
     }
 
@@ -0,0 +1,973 @@
+//! Converts the subset of LaTeX that most formulas are
+//! written in to MathML, so it can be laid out like any
+//! other formula
+
+use std::{
+    fmt::{Display, Write},
+    ops::Range,
+};
+
+/// A mistake in LaTeX, with the byte range
+/// of the source it was found in
+#[derive(Debug, Clone)]
+pub struct LatexError {
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+impl Display for LatexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for LatexError {}
+
+type Result<T> = std::result::Result<T, LatexError>;
+
+/// Converts LaTeX math, like `\frac{a}{b}`, to a `<math>` element
+pub fn latex_to_mathml(source: &str, display: bool) -> Result<String> {
+    let mut converter = Converter { source, pos: 0 };
+    let row = converter.expression(Until::End)?;
+    Ok(format!(
+        "<math{}>{}</math>",
+        if display { " display=\"block\"" } else { "" },
+        row
+    ))
+}
+
+/// What ends the expression being converted
+#[derive(Clone, Copy, PartialEq)]
+enum Until {
+    /// The end of the source
+    End,
+    /// A `}`, opened at this byte
+    Brace(usize),
+    /// A `\right`, for the `\left` at this byte
+    Right(usize),
+    /// A `&`, `\\` or `\end`, for the
+    /// `\begin` at this byte
+    Cell(usize),
+}
+
+struct Converter<'s> {
+    source: &'s str,
+    pos: usize,
+}
+
+impl<'s> Converter<'s> {
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '%' {
+                // Comments go to the end of the line
+                while self.bump().is_some_and(|c| c != '\n') {}
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn error<T>(&self, span: Range<usize>, message: impl Into<String>) -> Result<T> {
+        Err(LatexError {
+            span,
+            message: message.into(),
+        })
+    }
+
+    /// The character at the cursor, or the
+    /// last one at the end of the source
+    fn here(&self) -> Range<usize> {
+        match self.peek() {
+            Some(c) => self.pos..self.pos + c.len_utf8(),
+            None => {
+                let last = self.source[..self.pos]
+                    .char_indices()
+                    .next_back()
+                    .map(|(index, _)| index)
+                    .unwrap_or(self.pos);
+                last..self.pos
+            }
+        }
+    }
+
+    /// The name of the command at the cursor, without
+    /// moving past it
+    fn peek_command(&self) -> Option<&'s str> {
+        self.source[self.pos..]
+            .starts_with('\\')
+            .then(|| self.command_name(self.pos + 1))
+            .filter(|name| !name.is_empty())
+    }
+
+    /// The name of a command after its `\`, which is either
+    /// letters or a single other character
+    fn command_name(&self, at: usize) -> &'s str {
+        let rest = &self.source[at..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        if len == 0 {
+            rest.chars()
+                .next()
+                .map(|c| &rest[..c.len_utf8()])
+                .unwrap_or("")
+        } else {
+            &rest[..len]
+        }
+    }
+
+    /// Converts atoms up to whatever ends the expression
+    fn expression(&mut self, until: Until) -> Result<String> {
+        let mut row = String::new();
+        loop {
+            self.skip_whitespace();
+            let start = self.pos;
+            match (self.peek(), until) {
+                (None, Until::End) => break,
+                (None, Until::Brace(open)) => {
+                    return self.error(open..open + 1, "This `{` is never closed");
+                }
+                (None, Until::Right(left)) => {
+                    return self.error(left..left + 5, "This `\\left` has no `\\right`");
+                }
+                (None, Until::Cell(begin)) => {
+                    return self.error(begin..begin + 6, "This `\\begin` has no `\\end`");
+                }
+                (Some('}'), Until::Brace(_)) => {
+                    self.bump();
+                    break;
+                }
+                (Some('}'), _) => return self.error(start..start + 1, "Unmatched `}`"),
+                (Some('&'), Until::Cell(_)) => break,
+                (Some('&'), _) => {
+                    return self.error(start..start + 1, "`&` only goes in matrices");
+                }
+                (Some('^' | '_'), _) => {
+                    // A script with nothing before it
+                    let scripted = self.scripts(String::from("<mrow></mrow>"))?;
+                    row.push_str(&scripted);
+                }
+                (Some('\\'), _) => match (self.peek_command(), until) {
+                    (Some("right"), Until::Right(_)) => break,
+                    (Some("right"), _) => {
+                        return self.error(start..start + 6, "This `\\right` has no `\\left`");
+                    }
+                    (Some("\\" | "end"), Until::Cell(_)) => break,
+                    (Some("\\"), _) => {
+                        return self.error(start..start + 2, "`\\\\` only goes in matrices");
+                    }
+                    (Some("end"), _) => {
+                        return self.error(start..start + 4, "This `\\end` has no `\\begin`");
+                    }
+                    _ => {
+                        let atom = self.atom()?;
+                        row.push_str(&self.scripts(atom)?);
+                    }
+                },
+                _ => {
+                    let atom = self.atom()?;
+                    row.push_str(&self.scripts(atom)?);
+                }
+            }
+        }
+        Ok(row)
+    }
+
+    /// Attaches the `^` and `_` after an atom to it
+    fn scripts(&mut self, base: String) -> Result<String> {
+        let mut base = base;
+        let mut limits = false;
+        let mut sub: Option<String> = None;
+        let mut sup: Option<String> = None;
+        let mut primes: Option<String> = None;
+        loop {
+            self.skip_whitespace();
+            let start = self.pos;
+            let (slot, script) = match self.peek() {
+                Some('^') => (&mut sup, "^"),
+                Some('_') => (&mut sub, "_"),
+                // `'` is a superscript prime, which `x'^2` puts
+                // before the superscript
+                Some('\'') if primes.is_none() && sup.is_none() => {
+                    let mut prime = String::new();
+                    while self.peek() == Some('\'') {
+                        self.bump();
+                        prime.push('′');
+                    }
+                    primes = Some(format!("<mo>{}</mo>", prime));
+                    continue;
+                }
+                Some('\\') if sub.is_none() && sup.is_none() && primes.is_none() => {
+                    match self.peek_command() {
+                        Some("limits") => limits = true,
+                        Some("nolimits") => {
+                            limits = false;
+                            base = base.replacen("<mo>", "<mo movablelimits=\"false\">", 1);
+                        }
+                        _ => break,
+                    }
+                    self.pos += self.peek_command().map_or(0, |name| name.len() + 1);
+                    continue;
+                }
+                _ => break,
+            };
+            self.bump();
+            if slot.is_some() {
+                return self.error(
+                    start..start + 1,
+                    if script == "^" {
+                        "Double superscript, put braces around one of them"
+                    } else {
+                        "Double subscript, put braces around one of them"
+                    },
+                );
+            }
+            *slot = Some(self.argument(script)?);
+        }
+        let sup = match (primes, sup) {
+            (Some(primes), Some(sup)) => Some(format!("<mrow>{}{}</mrow>", primes, sup)),
+            (primes, sup) => primes.or(sup),
+        };
+        let (sub_tag, sup_tag, both_tag) = if limits {
+            ("munder", "mover", "munderover")
+        } else {
+            ("msub", "msup", "msubsup")
+        };
+        Ok(match (sub, sup) {
+            (None, None) => base,
+            (Some(sub), None) => format!("<{0}>{1}{2}</{0}>", sub_tag, base, sub),
+            (None, Some(sup)) => format!("<{0}>{1}{2}</{0}>", sup_tag, base, sup),
+            (Some(sub), Some(sup)) => {
+                format!("<{0}>{1}{2}{3}</{0}>", both_tag, base, sub, sup)
+            }
+        })
+    }
+
+    /// The argument of a command or script, which is a
+    /// group in braces or a single atom
+    fn argument(&mut self, of: &str) -> Result<String> {
+        self.skip_whitespace();
+        match self.peek() {
+            None | Some('}' | '&' | '^' | '_') => {
+                self.error(self.here(), format!("Missing the argument of `{}`", of))
+            }
+            Some('{') => self.atom(),
+            Some(c) if c.is_ascii_digit() => {
+                // `x^23` is `x^{2}3`
+                self.bump();
+                Ok(format!("<mn>{}</mn>", c))
+            }
+            _ => self.atom(),
+        }
+    }
+
+    /// The text inside of braces, for commands like `\text`
+    fn raw_argument(&mut self, of: &str) -> Result<(usize, &'s str)> {
+        self.skip_whitespace();
+        let open = self.pos;
+        if self.peek() != Some('{') {
+            return self.error(self.here(), format!("`{}` needs an argument in braces", of));
+        }
+        self.bump();
+        let mut depth = 0;
+        while let Some(c) = self.bump() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok((open + 1, &self.source[open + 1..self.pos - 1])),
+                '}' => depth -= 1,
+                '\\' => {
+                    self.bump();
+                }
+                _ => {}
+            }
+        }
+        self.error(open..open + 1, "This `{` is never closed")
+    }
+
+    fn atom(&mut self) -> Result<String> {
+        let start = self.pos;
+        let Some(c) = self.bump() else {
+            return self.error(start..start, "Expected math");
+        };
+        Ok(match c {
+            '{' => format!("<mrow>{}</mrow>", self.expression(Until::Brace(start))?),
+            '\\' => return self.command(start),
+            c if c.is_ascii_digit()
+                || (c == '.' && self.peek().is_some_and(|c| c.is_ascii_digit())) =>
+            {
+                while let Some(c) = self.peek() {
+                    let decimal_point = c == '.'
+                        && self.source[self.pos + 1..]
+                            .chars()
+                            .next()
+                            .is_some_and(|c| c.is_ascii_digit());
+                    if !c.is_ascii_digit() && !decimal_point {
+                        break;
+                    }
+                    self.bump();
+                }
+                format!("<mn>{}</mn>", &self.source[start..self.pos])
+            }
+            '(' | ')' | '[' | ']' | '|' => format!("<mo stretchy=\"false\">{}</mo>", c),
+            '-' => String::from("<mo>−</mo>"),
+            '*' => String::from("<mo>∗</mo>"),
+            '<' => String::from("<mo>&lt;</mo>"),
+            '>' => String::from("<mo>&gt;</mo>"),
+            '~' => String::from("<mspace width=\"0.25em\"/>"),
+            '\'' => String::from("<mo>′</mo>"),
+            '+' | '=' | '/' | ',' | ';' | ':' | '!' | '?' | '.' => format!("<mo>{}</mo>", c),
+            '#' | '$' => {
+                return self.error(start..self.pos, format!("`{}` can't be used in math", c));
+            }
+            c if c.is_alphabetic() => format!("<mi>{}</mi>", c),
+            c => format!("<mo>{}</mo>", escape(&c.to_string())),
+        })
+    }
+
+    fn command(&mut self, start: usize) -> Result<String> {
+        let name = self.command_name(self.pos);
+        self.pos += name.len();
+        let span = start..self.pos;
+        if name.is_empty() {
+            return self.error(span, "Expected a command after `\\`");
+        }
+
+        if let Some(letter) = greek(name) {
+            return Ok(if letter.is_uppercase() {
+                format!("<mi mathvariant=\"normal\">{}</mi>", letter)
+            } else {
+                format!("<mi>{}</mi>", letter)
+            });
+        }
+        if let Some(symbol) = identifier_symbol(name) {
+            return Ok(format!("<mi>{}</mi>", symbol));
+        }
+        if let Some(symbol) = operator_symbol(name) {
+            return Ok(format!("<mo>{}</mo>", escape(symbol)));
+        }
+        if let Some(bracket) = bracket_symbol(name) {
+            // Like `(`, brackets only stretch after `\left`
+            return Ok(format!("<mo stretchy=\"false\">{}</mo>", bracket));
+        }
+        if let Some(width) = space_width(name) {
+            return Ok(format!("<mspace width=\"{}\"/>", width));
+        }
+
+        Ok(match name {
+            "frac" | "dfrac" | "tfrac" | "cfrac" | "binom" => {
+                let numerator = self.argument(&format!("\\{}", name))?;
+                let denominator = self.argument(&format!("\\{}", name))?;
+                let fraction = if name == "binom" {
+                    format!(
+                        "<mrow><mo>(</mo><mfrac linethickness=\"0\">{}{}</mfrac><mo>)</mo></mrow>",
+                        numerator, denominator
+                    )
+                } else {
+                    format!("<mfrac>{}{}</mfrac>", numerator, denominator)
+                };
+                match name {
+                    "dfrac" | "cfrac" => {
+                        format!("<mstyle displaystyle=\"true\">{}</mstyle>", fraction)
+                    }
+                    "tfrac" => format!("<mstyle displaystyle=\"false\">{}</mstyle>", fraction),
+                    _ => fraction,
+                }
+            }
+            "sqrt" => {
+                self.skip_whitespace();
+                let index_start = self.pos;
+                if self.peek() == Some('[') {
+                    self.bump();
+                    let mut depth = 0;
+                    let index_end = loop {
+                        match self.bump() {
+                            Some('{') => depth += 1,
+                            Some('}') => depth -= 1,
+                            Some(']') if depth == 0 => break self.pos - 1,
+                            Some(_) => {}
+                            None => {
+                                return self.error(
+                                    index_start..index_start + 1,
+                                    "This `[` is never closed",
+                                );
+                            }
+                        }
+                    };
+                    // The index is converted on its own, with
+                    // its errors placed in the whole source
+                    let mut index = Converter {
+                        source: &self.source[..index_end],
+                        pos: index_start + 1,
+                    };
+                    let index = index.expression(Until::End)?;
+                    let radicand = self.argument("\\sqrt")?;
+                    format!("<mroot>{}<mrow>{}</mrow></mroot>", radicand, index)
+                } else {
+                    format!("<msqrt>{}</msqrt>", self.argument("\\sqrt")?)
+                }
+            }
+            "sin" | "cos" | "tan" | "cot" | "sec" | "csc" | "arcsin" | "arccos" | "arctan"
+            | "sinh" | "cosh" | "tanh" | "coth" | "log" | "ln" | "lg" | "exp" | "det" | "dim"
+            | "gcd" | "arg" | "deg" | "hom" | "ker" | "Pr" => format!("<mi>{}</mi>", name),
+            // These take limits under them, like `\sum`
+            "lim" | "max" | "min" | "sup" | "inf" => format!("<mo>{}</mo>", name),
+            "operatorname" => {
+                let (_, text) = self.raw_argument("\\operatorname")?;
+                format!("<mi mathvariant=\"normal\">{}</mi>", escape(text.trim()))
+            }
+            "mathbb" | "mathcal" | "mathfrak" => {
+                let (text_start, text) = self.raw_argument(&format!("\\{}", name))?;
+                let mut mapped = String::new();
+                for (offset, c) in text.char_indices() {
+                    if c.is_whitespace() {
+                        continue;
+                    }
+                    match styled_letter(name, c) {
+                        Some(c) => mapped.push(c),
+                        None => {
+                            let at = text_start + offset;
+                            return self.error(
+                                at..at + c.len_utf8(),
+                                format!("`\\{}` only works on letters", name),
+                            );
+                        }
+                    }
+                }
+                format!("<mi mathvariant=\"normal\">{}</mi>", mapped)
+            }
+            "mathrm" | "mathbf" | "mathit" | "mathsf" | "mathtt" | "boldsymbol" => {
+                let variant = match name {
+                    "mathrm" => "normal",
+                    "mathbf" | "boldsymbol" => "bold",
+                    "mathit" => "italic",
+                    "mathsf" => "sans-serif",
+                    _ => "monospace",
+                };
+                let argument = self.argument(&format!("\\{}", name))?;
+                format!(
+                    "<mstyle mathvariant=\"{}\">{}</mstyle>",
+                    variant,
+                    // Letters in the argument keep the variant
+                    // instead of being italic
+                    argument.replace("<mi>", &format!("<mi mathvariant=\"{}\">", variant))
+                )
+            }
+            "text" | "textrm" | "textit" | "textbf" | "mbox" => {
+                let (_, text) = self.raw_argument(&format!("\\{}", name))?;
+                format!("<mtext>{}</mtext>", escape(text))
+            }
+            "hat" | "widehat" | "bar" | "overline" | "vec" | "overrightarrow" | "dot" | "ddot"
+            | "tilde" | "widetilde" => {
+                let accent = match name {
+                    "hat" | "widehat" => "^",
+                    "bar" | "overline" => "¯",
+                    "vec" | "overrightarrow" => "→",
+                    "dot" => "˙",
+                    "ddot" => "¨",
+                    _ => "~",
+                };
+                let base = self.argument(&format!("\\{}", name))?;
+                format!("<mover accent=\"true\">{}<mo>{}</mo></mover>", base, accent)
+            }
+            "underline" => {
+                let base = self.argument("\\underline")?;
+                format!("<munder accent=\"true\">{}<mo>_</mo></munder>", base)
+            }
+            "left" => {
+                let open = self.delimiter("\\left")?;
+                let inner = self.expression(Until::Right(start))?;
+                // Past the `\right`
+                self.pos += "\\right".len();
+                let close = self.delimiter("\\right")?;
+                format!("<mrow>{}{}{}</mrow>", open, inner, close)
+            }
+            "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr" | "Bigl" | "Bigr" | "biggl"
+            | "biggr" | "Biggl" | "Biggr" => {
+                let delimiter = self.delimiter(&format!("\\{}", name))?;
+                delimiter.replacen("<mo>", "<mo stretchy=\"false\">", 1)
+            }
+            "begin" => self.environment(start)?,
+            "limits" | "nolimits" => {
+                return self.error(span, format!("`\\{}` only goes after an operator", name));
+            }
+            _ => return self.error(span, format!("Unknown command `\\{}`", name)),
+        })
+    }
+
+    /// The bracket after `\left`, `\right` or `\big`,
+    /// where `.` is no bracket at all
+    fn delimiter(&mut self, of: &str) -> Result<String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let delimiter = match self.bump() {
+            Some('.') => return Ok(String::new()),
+            Some(c @ ('(' | ')' | '[' | ']' | '|' | '/')) => c.to_string(),
+            Some('<') => String::from("⟨"),
+            Some('>') => String::from("⟩"),
+            Some('\\') => {
+                let name = self.command_name(self.pos);
+                self.pos += name.len();
+                match bracket_symbol(name) {
+                    Some(bracket) => bracket.to_string(),
+                    None => {
+                        return self.error(
+                            start..self.pos,
+                            format!("`\\{}` isn't a bracket `{}` can use", name, of),
+                        );
+                    }
+                }
+            }
+            _ => {
+                return self.error(
+                    start..self.pos,
+                    format!("`{}` needs a bracket like `(`, or `.` for none", of),
+                );
+            }
+        };
+        Ok(format!("<mo>{}</mo>", delimiter))
+    }
+
+    /// Converts `\begin{name}` through `\end{name}`
+    fn environment(&mut self, begin: usize) -> Result<String> {
+        let (name_start, name) = self.raw_argument("\\begin")?;
+        let name = name.trim();
+        let (open, close, align) = match name {
+            "matrix" | "smallmatrix" | "array" | "aligned" | "align" | "align*" | "gathered" => {
+                ("", "", None)
+            }
+            "pmatrix" => ("(", ")", None),
+            "bmatrix" => ("[", "]", None),
+            "Bmatrix" => ("{", "}", None),
+            "vmatrix" => ("|", "|", None),
+            "Vmatrix" => ("‖", "‖", None),
+            "cases" => ("{", "", Some("left")),
+            _ => {
+                return self.error(
+                    name_start..name_start + name.len(),
+                    format!("Unknown environment `{}`", name),
+                );
+            }
+        };
+        if name == "array" {
+            // The column spec, like `{cc}`, which
+            // doesn't change the layout here
+            self.raw_argument("\\begin{array}")?;
+        }
+
+        let mut table = String::new();
+        let mut row = String::new();
+        loop {
+            let cell = self.expression(Until::Cell(begin))?;
+            match align {
+                Some(align) => write!(row, "<mtd columnalign=\"{}\">{}</mtd>", align, cell),
+                None => write!(row, "<mtd>{}</mtd>", cell),
+            }
+            .unwrap();
+            if self.peek() == Some('&') {
+                self.bump();
+                continue;
+            }
+            if self.peek_command() == Some("\\") {
+                self.pos += 2;
+                write!(table, "<mtr>{}</mtr>", row).unwrap();
+                row.clear();
+                continue;
+            }
+            // `\end`
+            self.pos += "\\end".len();
+            let (end_name_start, end_name) = self.raw_argument("\\end")?;
+            if end_name.trim() != name {
+                return self.error(
+                    end_name_start..end_name_start + end_name.len(),
+                    format!(
+                        "`\\begin{{{}}}` is closed by `\\end{{{}}}`",
+                        name,
+                        end_name.trim()
+                    ),
+                );
+            }
+            // A `\\` before `\end` doesn't start another row
+            if row != "<mtd></mtd>" || table.is_empty() {
+                write!(table, "<mtr>{}</mtr>", row).unwrap();
+            }
+            break;
+        }
+
+        let mut converted = String::from("<mrow>");
+        if !open.is_empty() {
+            write!(converted, "<mo>{}</mo>", open).unwrap();
+        }
+        write!(converted, "<mtable>{}</mtable>", table).unwrap();
+        if !close.is_empty() {
+            write!(converted, "<mo>{}</mo>", close).unwrap();
+        }
+        converted.push_str("</mrow>");
+        Ok(converted)
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn greek(name: &str) -> Option<char> {
+    Some(match name {
+        "alpha" => 'α',
+        "beta" => 'β',
+        "gamma" => 'γ',
+        "delta" => 'δ',
+        "epsilon" => 'ϵ',
+        "varepsilon" => 'ε',
+        "zeta" => 'ζ',
+        "eta" => 'η',
+        "theta" => 'θ',
+        "vartheta" => 'ϑ',
+        "iota" => 'ι',
+        "kappa" => 'κ',
+        "lambda" => 'λ',
+        "mu" => 'μ',
+        "nu" => 'ν',
+        "xi" => 'ξ',
+        "omicron" => 'ο',
+        "pi" => 'π',
+        "varpi" => 'ϖ',
+        "rho" => 'ρ',
+        "varrho" => 'ϱ',
+        "sigma" => 'σ',
+        "varsigma" => 'ς',
+        "tau" => 'τ',
+        "upsilon" => 'υ',
+        "phi" => 'ϕ',
+        "varphi" => 'φ',
+        "chi" => 'χ',
+        "psi" => 'ψ',
+        "omega" => 'ω',
+        "Gamma" => 'Γ',
+        "Delta" => 'Δ',
+        "Theta" => 'Θ',
+        "Lambda" => 'Λ',
+        "Xi" => 'Ξ',
+        "Pi" => 'Π',
+        "Sigma" => 'Σ',
+        "Upsilon" => 'Υ',
+        "Phi" => 'Φ',
+        "Psi" => 'Ψ',
+        "Omega" => 'Ω',
+        _ => return None,
+    })
+}
+
+/// Symbols that are used like variables
+fn identifier_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "emptyset" | "varnothing" => "∅",
+        "ell" => "ℓ",
+        "hbar" => "ℏ",
+        "aleph" => "ℵ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "angle" => "∠",
+        "triangle" => "△",
+        _ => return None,
+    })
+}
+
+/// Commands that are brackets, which `\left`, `\right` and `\big` take too
+fn bracket_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "{" | "lbrace" => "{",
+        "}" | "rbrace" => "}",
+        "|" | "Vert" | "lVert" | "rVert" => "‖",
+        "vert" | "lvert" | "rvert" => "|",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        _ => return None,
+    })
+}
+
+fn operator_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "bigcup" => "⋃",
+        "bigcap" => "⋂",
+        "bigvee" => "⋁",
+        "bigwedge" => "⋀",
+        "bigoplus" => "⨁",
+        "bigotimes" => "⨂",
+        "le" | "leq" => "≤",
+        "ge" | "geq" => "≥",
+        "ne" | "neq" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "ll" => "≪",
+        "gg" => "≫",
+        "prec" => "≺",
+        "succ" => "≻",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "supset" => "⊃",
+        "subseteq" => "⊆",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "wedge" | "land" => "∧",
+        "vee" | "lor" => "∨",
+        "neg" | "lnot" => "¬",
+        "forall" => "∀",
+        "exists" => "∃",
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "implies" => "⟹",
+        "mapsto" => "↦",
+        "times" => "×",
+        "div" => "÷",
+        "cdot" => "⋅",
+        "pm" => "±",
+        "mp" => "∓",
+        "ast" => "∗",
+        "circ" => "∘",
+        "bullet" => "∙",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "setminus" => "∖",
+        "mid" => "∣",
+        "parallel" => "∥",
+        "perp" => "⊥",
+        "prime" => "′",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "colon" => ":",
+        "%" => "%",
+        "$" => "$",
+        "#" => "#",
+        "&" => "&",
+        "_" => "_",
+        _ => return None,
+    })
+}
+
+fn space_width(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "," | "thinspace" => "0.1667em",
+        ":" | ">" | "medspace" => "0.2222em",
+        ";" | "thickspace" => "0.2778em",
+        "!" | "negthinspace" => "-0.1667em",
+        " " => "0.25em",
+        "quad" => "1em",
+        "qquad" => "2em",
+        _ => return None,
+    })
+}
+
+/// The letter in the alphabet of `\mathbb`, `\mathcal` or
+/// `\mathfrak`, some of which are outside of the block of
+/// math letters because they were in Unicode first
+fn styled_letter(command: &str, c: char) -> Option<char> {
+    let exception = match (command, c) {
+        ("mathbb", 'C') => Some('ℂ'),
+        ("mathbb", 'H') => Some('ℍ'),
+        ("mathbb", 'N') => Some('ℕ'),
+        ("mathbb", 'P') => Some('ℙ'),
+        ("mathbb", 'Q') => Some('ℚ'),
+        ("mathbb", 'R') => Some('ℝ'),
+        ("mathbb", 'Z') => Some('ℤ'),
+        ("mathcal", 'B') => Some('ℬ'),
+        ("mathcal", 'E') => Some('ℰ'),
+        ("mathcal", 'F') => Some('ℱ'),
+        ("mathcal", 'H') => Some('ℋ'),
+        ("mathcal", 'I') => Some('ℐ'),
+        ("mathcal", 'L') => Some('ℒ'),
+        ("mathcal", 'M') => Some('ℳ'),
+        ("mathcal", 'R') => Some('ℛ'),
+        ("mathcal", 'e') => Some('ℯ'),
+        ("mathcal", 'g') => Some('ℊ'),
+        ("mathcal", 'o') => Some('ℴ'),
+        ("mathfrak", 'C') => Some('ℭ'),
+        ("mathfrak", 'H') => Some('ℌ'),
+        ("mathfrak", 'I') => Some('ℑ'),
+        ("mathfrak", 'R') => Some('ℜ'),
+        ("mathfrak", 'Z') => Some('ℨ'),
+        _ => None,
+    };
+    if exception.is_some() {
+        return exception;
+    }
+    let (upper, lower) = match command {
+        "mathbb" => (0x1D538, 0x1D552),
+        "mathcal" => (0x1D49C, 0x1D4B6),
+        _ => (0x1D504, 0x1D51E),
+    };
+    let code = match c {
+        'A'..='Z' => upper + (c as u32 - 'A' as u32),
+        'a'..='z' => lower + (c as u32 - 'a' as u32),
+        '0'..='9' if command == "mathbb" => 0x1D7D8 + (c as u32 - '0' as u32),
+        _ => return None,
+    };
+    char::from_u32(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(source: &str) -> String {
+        let math = latex_to_mathml(source, false).unwrap();
+        math["<math>".len()..math.len() - "</math>".len()].to_string()
+    }
+
+    fn error(source: &str) -> (Range<usize>, String) {
+        let error = latex_to_mathml(source, false).unwrap_err();
+        (error.span, error.message)
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            latex_to_mathml("x", true).unwrap(),
+            "<math display=\"block\"><mi>x</mi></math>"
+        );
+    }
+
+    #[test]
+    fn atoms() {
+        assert_eq!(convert("x + 12.5"), "<mi>x</mi><mo>+</mo><mn>12.5</mn>");
+        assert_eq!(convert("a < b"), "<mi>a</mi><mo>&lt;</mo><mi>b</mi>");
+        assert_eq!(
+            convert("\\alpha\\Gamma"),
+            "<mi>α</mi><mi mathvariant=\"normal\">Γ</mi>"
+        );
+    }
+
+    #[test]
+    fn fractions() {
+        assert_eq!(
+            convert("\\frac{a}{b}"),
+            "<mfrac><mrow><mi>a</mi></mrow><mrow><mi>b</mi></mrow></mfrac>"
+        );
+        assert_eq!(convert("\\frac12"), "<mfrac><mn>1</mn><mn>2</mn></mfrac>");
+    }
+
+    #[test]
+    fn scripts() {
+        assert_eq!(convert("x^2"), "<msup><mi>x</mi><mn>2</mn></msup>");
+        assert_eq!(
+            convert("x^23"),
+            "<msup><mi>x</mi><mn>2</mn></msup><mn>3</mn>"
+        );
+        assert_eq!(
+            convert("x_i^2"),
+            "<msubsup><mi>x</mi><mi>i</mi><mn>2</mn></msubsup>"
+        );
+        assert_eq!(convert("f''"), "<msup><mi>f</mi><mo>′′</mo></msup>");
+        assert_eq!(
+            convert("x'^2"),
+            "<msup><mi>x</mi><mrow><mo>′</mo><mn>2</mn></mrow></msup>"
+        );
+    }
+
+    #[test]
+    fn limits() {
+        assert_eq!(
+            convert("\\sum\\limits_i"),
+            "<munder><mo>∑</mo><mi>i</mi></munder>"
+        );
+        assert_eq!(
+            convert("\\sum\\nolimits_i^n"),
+            "<msubsup><mo movablelimits=\"false\">∑</mo><mi>i</mi><mi>n</mi></msubsup>"
+        );
+    }
+
+    #[test]
+    fn brackets() {
+        assert_eq!(
+            convert("\\{x\\}"),
+            "<mo stretchy=\"false\">{</mo><mi>x</mi><mo stretchy=\"false\">}</mo>"
+        );
+        assert_eq!(
+            convert("\\left\\{x\\right."),
+            "<mrow><mo>{</mo><mi>x</mi></mrow>"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error("x^"),
+            (1..2, String::from("Missing the argument of `^`"))
+        );
+        assert_eq!(
+            error("x_{a}_"),
+            (
+                5..6,
+                String::from("Double subscript, put braces around one of them")
+            )
+        );
+        assert_eq!(
+            error("x^2^3"),
+            (
+                3..4,
+                String::from("Double superscript, put braces around one of them")
+            )
+        );
+        assert_eq!(
+            error("a + \\foo"),
+            (4..8, String::from("Unknown command `\\foo`"))
+        );
+        assert_eq!(
+            error("\\limits"),
+            (0..7, String::from("`\\limits` only goes after an operator"))
+        );
+        assert_eq!(
+            error("{x"),
+            (0..1, String::from("This `{` is never closed"))
+        );
+        assert_eq!(error("x}"), (1..2, String::from("Unmatched `}`")));
+    }
+}
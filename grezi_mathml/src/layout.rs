@@ -0,0 +1,728 @@
+//! Lays MathML out in the spirit of TeX, with boxes that have
+//! a width, and a height above and below their baseline.
+//! Coordinates go right and down from the start of the
+//! baseline while a formula is being laid out
+
+use egui_glyphon::cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping, Style, Weight};
+use roxmltree::Node;
+
+/// Size of scripts relative to what they're attached to
+const SCRIPT_SCALE: f32 = 0.7;
+/// Scripts don't get smaller than this, relative to the formula
+const MIN_SCRIPT_SCALE: f32 = 0.5;
+/// How far above the baseline fraction bars and the middle of
+/// stretched brackets are
+const AXIS_HEIGHT: f32 = 0.25;
+/// How far text goes above and below the baseline
+const ASCENT: f32 = 0.75;
+const DESCENT: f32 = 0.25;
+const RULE_THICKNESS: f32 = 0.06;
+/// How much bigger big operators like `∑` are in display formulas
+const DISPLAY_OPERATOR_SCALE: f32 = 1.5;
+
+const RELATIONS: &str = "=<>≤≥≠≈≡∼≃≅∝→←↔⇒⇐⇔↦∈∉∋⊂⊃⊆⊇≺≻⪯⪰∣∥⊥:";
+const BINARY_OPERATORS: &str = "+−-×÷·∗±∓∪∩∧∨∘⊕⊖⊗⊙⋅∖";
+const BIG_OPERATORS: &str = "∑∏∐∫∬∭∮⋃⋂⋁⋀⨁⨂";
+const FENCES: &str = "()[]{}|‖⟨⟩⌈⌉⌊⌋";
+
+/// A formula, laid out with its top left corner at 0, 0
+#[derive(Default)]
+pub struct MathLayout {
+    pub runs: Vec<MathRun>,
+    pub rules: Vec<MathRule>,
+    pub width: f32,
+    /// How far the formula goes above its baseline
+    pub ascent: f32,
+    /// How far the formula goes below its baseline
+    pub descent: f32,
+}
+
+impl MathLayout {
+    pub fn height(&self) -> f32 {
+        self.ascent + self.descent
+    }
+}
+
+/// Text of a formula, with its buffer placed at `pos`
+pub struct MathRun {
+    pub buffer: Buffer,
+    pub pos: [f32; 2],
+}
+
+/// A line of a formula, like a fraction bar
+#[derive(Clone, Copy)]
+pub struct MathRule {
+    pub from: [f32; 2],
+    pub to: [f32; 2],
+    pub thickness: f32,
+}
+
+#[derive(Default)]
+struct MathBox {
+    width: f32,
+    ascent: f32,
+    descent: f32,
+    runs: Vec<MathRun>,
+    rules: Vec<MathRule>,
+}
+
+impl MathBox {
+    fn empty(width: f32) -> Self {
+        MathBox {
+            width,
+            ..Default::default()
+        }
+    }
+
+    fn translate(&mut self, x: f32, y: f32) {
+        for run in &mut self.runs {
+            run.pos[0] += x;
+            run.pos[1] += y;
+        }
+        for rule in &mut self.rules {
+            rule.from[0] += x;
+            rule.from[1] += y;
+            rule.to[0] += x;
+            rule.to[1] += y;
+        }
+    }
+
+    /// Puts `other` into this box with its baseline starting at
+    /// `x`, `y`, growing this box to fit it
+    fn place(&mut self, mut other: MathBox, x: f32, y: f32) {
+        other.translate(x, y);
+        self.width = self.width.max(x + other.width);
+        self.ascent = self.ascent.max(other.ascent - y);
+        self.descent = self.descent.max(other.descent + y);
+        self.runs.append(&mut other.runs);
+        self.rules.append(&mut other.rules);
+    }
+
+    fn rule(&mut self, from: [f32; 2], to: [f32; 2], thickness: f32) {
+        self.rules.push(MathRule {
+            from,
+            to,
+            thickness,
+        });
+    }
+}
+
+#[derive(Clone, Copy)]
+struct MathStyle {
+    font_size: f32,
+    display: bool,
+    /// Whether this is in a script, where
+    /// operators aren't spaced out
+    script: bool,
+}
+
+impl MathStyle {
+    fn script(self, base_size: f32) -> Self {
+        MathStyle {
+            font_size: (self.font_size * SCRIPT_SCALE).max(base_size * MIN_SCRIPT_SCALE),
+            display: false,
+            script: true,
+        }
+    }
+
+    fn text(self) -> Self {
+        MathStyle {
+            display: false,
+            ..self
+        }
+    }
+}
+
+struct Layouter<'a, 'b> {
+    font_system: &'b mut FontSystem,
+    attrs: &'b Attrs<'a>,
+    /// Size of the formula itself
+    base_size: f32,
+}
+
+pub fn layout(
+    node: Node,
+    font_system: &mut FontSystem,
+    attrs: &Attrs,
+    font_size: f32,
+    display: bool,
+) -> MathLayout {
+    let mut layouter = Layouter {
+        font_system,
+        attrs,
+        base_size: font_size,
+    };
+    let mut math = layouter.row(
+        node.children().filter(Node::is_element),
+        MathStyle {
+            font_size,
+            display,
+            script: false,
+        },
+    );
+    math.translate(0.0, math.ascent);
+    MathLayout {
+        runs: math.runs,
+        rules: math.rules,
+        width: math.width,
+        ascent: math.ascent,
+        descent: math.descent,
+    }
+}
+
+impl Layouter<'_, '_> {
+    fn node(&mut self, node: Node, style: MathStyle) -> MathBox {
+        let size = style.font_size;
+        match node.tag_name().name() {
+            "mi" => {
+                let text = token_text(node);
+                let italic = text.chars().count() == 1
+                    && node
+                        .attribute("mathvariant")
+                        .is_none_or(|v| v.ends_with("italic"));
+                self.styled_text(&text, size, italic, is_bold(node))
+            }
+            "mn" | "mtext" => self.styled_text(&token_text(node), size, false, is_bold(node)),
+            "ms" => self.text(&format!("\"{}\"", token_text(node)), size, false),
+            "mo" => self.operator(node, style, false),
+            "mspace" => MathBox::empty(
+                node.attribute("width")
+                    .and_then(|width| parse_length(width, size))
+                    .unwrap_or_default(),
+            ),
+            "mfrac" => self.fraction(node, style),
+            "msup" | "msub" | "msubsup" => self.scripts(node, style),
+            "munder" | "mover" | "munderover" => self.under_over(node, style),
+            "msqrt" => {
+                let content = self.row(node.children().filter(Node::is_element), style);
+                self.radical(content, None, style)
+            }
+            "mroot" => {
+                let mut children = node.children().filter(Node::is_element);
+                let content = match children.next() {
+                    Some(content) => self.node(content, style),
+                    None => MathBox::default(),
+                };
+                let index_style = style.script(self.base_size).script(self.base_size);
+                let index = children.next().map(|index| self.node(index, index_style));
+                self.radical(content, index, style)
+            }
+            "mtable" => self.table(node, style),
+            "mfenced" => self.fenced(node, style),
+            "mphantom" => {
+                let mut phantom = self.row(node.children().filter(Node::is_element), style);
+                phantom.runs.clear();
+                phantom.rules.clear();
+                phantom
+            }
+            "mstyle" => {
+                let style = match node.attribute("displaystyle") {
+                    Some("true") => MathStyle {
+                        display: true,
+                        ..style
+                    },
+                    Some("false") => style.text(),
+                    _ => style,
+                };
+                self.row(node.children().filter(Node::is_element), style)
+            }
+            // Only the first child of `semantics` is presentation markup
+            "semantics" => match node.first_element_child() {
+                Some(child) => self.node(child, style),
+                None => MathBox::default(),
+            },
+            "annotation" | "annotation-xml" | "none" | "mprescripts" => MathBox::default(),
+            // `mrow`, `mpadded`, `merror`, `mtd`, `math`
+            // and anything that isn't understood
+            _ => self.row(node.children().filter(Node::is_element), style),
+        }
+    }
+
+    /// Lays nodes out side by side, and stretches the
+    /// brackets among them to the height of the rest
+    fn row<'n>(&mut self, nodes: impl Iterator<Item = Node<'n, 'n>>, style: MathStyle) -> MathBox {
+        let nodes: Vec<Node> = nodes.collect();
+        let mut boxes: Vec<Option<MathBox>> = Vec::with_capacity(nodes.len());
+        let (mut ascent, mut descent) = (0.0f32, 0.0f32);
+        for (index, node) in nodes.iter().enumerate() {
+            if is_fence(*node) {
+                boxes.push(None);
+                continue;
+            }
+            let child = if node.tag_name().name() == "mo" {
+                self.operator(*node, style, index == 0)
+            } else {
+                self.node(*node, style)
+            };
+            ascent = ascent.max(child.ascent);
+            descent = descent.max(child.descent);
+            boxes.push(Some(child));
+        }
+
+        let mut row = MathBox::default();
+        let mut x = 0.0;
+        for (node, child) in nodes.iter().zip(boxes) {
+            let child = match child {
+                Some(child) => child,
+                None => self.fence(&token_text(*node), ascent, descent, style),
+            };
+            let width = child.width;
+            row.place(child, x, 0.0);
+            x += width;
+        }
+        row
+    }
+
+    /// A bracket tall enough to cover `ascent` and `descent`
+    fn fence(&mut self, text: &str, ascent: f32, descent: f32, style: MathStyle) -> MathBox {
+        let size = style.font_size;
+        let axis = size * AXIS_HEIGHT;
+        // Brackets are stretched evenly around the axis
+        let half_height = (ascent - axis).max(descent + axis);
+        let fence_size = (half_height * 2.0 / (ASCENT + DESCENT)).max(size);
+        let mut fence = self.text(text, fence_size, false);
+        let shift = fence_size * (ASCENT - DESCENT) / 2.0 - axis;
+        fence.translate(0.0, shift);
+        fence.ascent -= shift;
+        fence.descent += shift;
+        fence
+    }
+
+    fn operator(&mut self, node: Node, style: MathStyle, first: bool) -> MathBox {
+        let mut text = token_text(node);
+        if text == "-" {
+            text = "−".to_string();
+        }
+        let size = style.font_size;
+        let big = is_big_operator(&text);
+        let operator_size = if big && style.display {
+            size * DISPLAY_OPERATOR_SCALE
+        } else {
+            size
+        };
+        let mut operator = self.text(&text, operator_size, false);
+        if big && style.display {
+            // Centered on the axis, like brackets
+            let shift = operator_size * (ASCENT - DESCENT) / 2.0 - size * AXIS_HEIGHT;
+            operator.translate(0.0, shift);
+            operator.ascent -= shift;
+            operator.descent += shift;
+        }
+
+        // Spacing in eighteenths of an em, like TeX
+        let (left, right) = match text.as_str() {
+            _ if style.script => (0.0, 0.0),
+            t if t.chars().count() == 1 && RELATIONS.contains(t) => (5.0, 5.0),
+            // A minus at the start of a row is a sign
+            t if t.chars().count() == 1 && BINARY_OPERATORS.contains(t) && !first => (4.0, 4.0),
+            "," | ";" => (0.0, 3.0),
+            _ if big => (0.0, 3.0),
+            _ => (0.0, 0.0),
+        };
+        let left = node
+            .attribute("lspace")
+            .and_then(|space| parse_length(space, size))
+            .unwrap_or(left * size / 18.0);
+        let right = node
+            .attribute("rspace")
+            .and_then(|space| parse_length(space, size))
+            .unwrap_or(right * size / 18.0);
+        let mut spaced = MathBox::empty(left + operator.width + right);
+        spaced.place(operator, left, 0.0);
+        spaced
+    }
+
+    fn fraction(&mut self, node: Node, style: MathStyle) -> MathBox {
+        let size = style.font_size;
+        let part_style = if style.display {
+            style.text()
+        } else {
+            style.script(self.base_size)
+        };
+        let mut children = node.children().filter(Node::is_element);
+        let numerator = match children.next() {
+            Some(numerator) => self.node(numerator, part_style),
+            None => MathBox::default(),
+        };
+        let denominator = match children.next() {
+            Some(denominator) => self.node(denominator, part_style),
+            None => MathBox::default(),
+        };
+
+        let thickness = node
+            .attribute("linethickness")
+            .and_then(|thickness| parse_length(thickness, size))
+            .unwrap_or(size * RULE_THICKNESS);
+        let axis = size * AXIS_HEIGHT;
+        let gap = size * 0.15;
+        let padding = size * 0.1;
+        let width = numerator.width.max(denominator.width) + padding * 2.0;
+
+        let mut fraction = MathBox::empty(width);
+        let numerator_x = (width - numerator.width) / 2.0;
+        let numerator_y = -(axis + thickness / 2.0 + gap + numerator.descent);
+        fraction.place(numerator, numerator_x, numerator_y);
+        let denominator_x = (width - denominator.width) / 2.0;
+        let denominator_y = -axis + thickness / 2.0 + gap + denominator.ascent;
+        fraction.place(denominator, denominator_x, denominator_y);
+        if thickness > 0.0 {
+            fraction.rule(
+                [padding / 2.0, -axis],
+                [width - padding / 2.0, -axis],
+                thickness,
+            );
+        }
+        fraction
+    }
+
+    fn scripts(&mut self, node: Node, style: MathStyle) -> MathBox {
+        let mut children = node.children().filter(Node::is_element);
+        let Some(base_node) = children.next() else {
+            return MathBox::default();
+        };
+        // Limits of big operators go under and over them in display formulas
+        if style.display && is_big_operator_node(base_node) {
+            return self.under_over(node, style);
+        }
+
+        let size = style.font_size;
+        let script_style = style.script(self.base_size);
+        let (sub_node, sup_node) = match node.tag_name().name() {
+            "msub" => (children.next(), None),
+            "msup" => (None, children.next()),
+            _ => (children.next(), children.next()),
+        };
+        let base = self.node(base_node, style);
+        let sub = sub_node.map(|sub| self.node(sub, script_style));
+        let sup = sup_node.map(|sup| self.node(sup, script_style));
+
+        let base_width = base.width;
+        let base_ascent = base.ascent;
+        let base_descent = base.descent;
+        let mut scripts = MathBox::default();
+        scripts.place(base, 0.0, 0.0);
+        let gap = size * 0.05;
+        let mut width = base_width;
+        let mut sup_y = None;
+        if let Some(sup) = sup {
+            let shift = (size * 0.45)
+                .max(base_ascent - sup.ascent * 0.5)
+                .max(sup.descent + size * 0.25);
+            sup_y = Some(-shift);
+            width = width.max(base_width + gap + sup.width);
+            scripts.place(sup, base_width + gap, -shift);
+        }
+        if let Some(sub) = sub {
+            let mut shift = (size * 0.2)
+                .max(base_descent + sub.ascent * 0.25)
+                .max(sub.ascent - size * 0.5);
+            // Keep scripts that are both there from touching
+            if let Some(sup_y) = sup_y {
+                shift = shift.max(sup_y + size * 0.5 + sub.ascent);
+            }
+            width = width.max(base_width + gap + sub.width);
+            scripts.place(sub, base_width + gap, shift);
+        }
+        scripts.width = width + gap;
+        scripts
+    }
+
+    fn under_over(&mut self, node: Node, style: MathStyle) -> MathBox {
+        let mut children = node.children().filter(Node::is_element);
+        let Some(base_node) = children.next() else {
+            return MathBox::default();
+        };
+        let (under_node, over_node) = match node.tag_name().name() {
+            "munder" | "msub" => (children.next(), None),
+            "mover" | "msup" => (None, children.next()),
+            _ => (children.next(), children.next()),
+        };
+        let size = style.font_size;
+        let accent = node.attribute("accent") == Some("true");
+        let script_style = if accent {
+            style
+        } else {
+            style.script(self.base_size)
+        };
+        let base = self.node(base_node, style);
+        let under = under_node.map(|under| self.node(under, script_style));
+        let over = over_node.map(|over| self.node(over, script_style));
+
+        let width = [Some(&base), under.as_ref(), over.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|part| part.width)
+            .fold(0.0, f32::max);
+        let gap = if accent { 0.0 } else { size * 0.1 };
+        let base_ascent = base.ascent;
+        let base_descent = base.descent;
+        let mut under_over = MathBox::empty(width);
+        let base_x = (width - base.width) / 2.0;
+        under_over.place(base, base_x, 0.0);
+        if let Some(over) = over {
+            // Accents sit right on top of what they're on
+            let y = if accent {
+                -(base_ascent - size * 0.25 + over.descent)
+            } else {
+                -(base_ascent + gap + over.descent)
+            };
+            let x = (width - over.width) / 2.0;
+            under_over.place(over, x, y);
+        }
+        if let Some(under) = under {
+            let x = (width - under.width) / 2.0;
+            under_over.place(under, x, base_descent + gap + under.ascent);
+        }
+        under_over
+    }
+
+    fn radical(&mut self, content: MathBox, index: Option<MathBox>, style: MathStyle) -> MathBox {
+        let size = style.font_size;
+        let thickness = size * RULE_THICKNESS;
+        let gap = size * 0.12;
+        let sign_width = size * 0.55;
+        let top = -(content.ascent.max(size * ASCENT * 0.8) + gap);
+        let bottom = content.descent.max(size * DESCENT * 0.5);
+        let middle = top + (bottom - top) * 0.6;
+
+        let mut radical = MathBox::default();
+        // The index goes over the tick of the sign
+        let offset = match index {
+            Some(index) => {
+                let offset = (index.width - sign_width * 0.5).max(0.0);
+                let y = middle - size * 0.1 - index.descent;
+                radical.place(index, 0.0, y);
+                offset
+            }
+            None => 0.0,
+        };
+        let content_width = content.width;
+        radical.place(content, offset + sign_width + gap / 2.0, 0.0);
+        let end = offset + sign_width + content_width + gap;
+        radical.rule(
+            [offset, middle],
+            [offset + sign_width * 0.2, middle - size * 0.05],
+            thickness,
+        );
+        radical.rule(
+            [offset + sign_width * 0.2, middle - size * 0.05],
+            [offset + sign_width * 0.5, bottom],
+            thickness * 1.5,
+        );
+        radical.rule(
+            [offset + sign_width * 0.5, bottom],
+            [offset + sign_width, top],
+            thickness,
+        );
+        radical.rule([offset + sign_width, top], [end, top], thickness);
+        radical.width = end + gap / 2.0;
+        radical.ascent = radical.ascent.max(-top + thickness);
+        radical.descent = radical.descent.max(bottom);
+        radical
+    }
+
+    fn table(&mut self, node: Node, style: MathStyle) -> MathBox {
+        let size = style.font_size;
+        let cell_style = style.text();
+        let rows: Vec<Vec<MathBox>> = node
+            .children()
+            .filter(|row| row.is_element() && matches!(row.tag_name().name(), "mtr" | "mlabeledtr"))
+            .map(|row| {
+                row.children()
+                    .filter(Node::is_element)
+                    .map(|cell| self.node(cell, cell_style))
+                    .collect()
+            })
+            .collect();
+
+        let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+        let mut widths = vec![0.0f32; columns];
+        for row in &rows {
+            for (column, cell) in row.iter().enumerate() {
+                widths[column] = widths[column].max(cell.width);
+            }
+        }
+        let column_gap = size * 0.8;
+        let row_gap = size * 0.3;
+        let heights: Vec<(f32, f32)> = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .fold((size * ASCENT, size * DESCENT), |(a, d), cell| {
+                        (a.max(cell.ascent), d.max(cell.descent))
+                    })
+            })
+            .collect();
+        let total_height = heights.iter().map(|(a, d)| a + d).sum::<f32>()
+            + row_gap * rows.len().saturating_sub(1) as f32;
+
+        // Tables are centered on the axis
+        let mut table = MathBox::default();
+        let mut y = -(size * AXIS_HEIGHT + total_height / 2.0);
+        for (row, (ascent, descent)) in rows.into_iter().zip(heights) {
+            y += ascent;
+            let mut x = 0.0;
+            for (cell, width) in row.into_iter().zip(&widths) {
+                let cell_x = x + (width - cell.width) / 2.0;
+                table.place(cell, cell_x, y);
+                x += width + column_gap;
+            }
+            y += descent + row_gap;
+        }
+        table.width = widths.iter().sum::<f32>() + column_gap * columns.saturating_sub(1) as f32;
+        table.ascent = table.ascent.max(size * AXIS_HEIGHT + total_height / 2.0);
+        table.descent = table.descent.max(total_height / 2.0 - size * AXIS_HEIGHT);
+        table
+    }
+
+    /// `<mfenced>`, which puts brackets around its
+    /// children, and separators between them
+    fn fenced(&mut self, node: Node, style: MathStyle) -> MathBox {
+        let open = node.attribute("open").unwrap_or("(");
+        let close = node.attribute("close").unwrap_or(")");
+        let separators: Vec<char> = node
+            .attribute("separators")
+            .unwrap_or(",")
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+
+        let mut inner = MathBox::default();
+        let mut x = 0.0;
+        for (index, child) in node.children().filter(Node::is_element).enumerate() {
+            if index != 0 {
+                if let Some(separator) = separators
+                    .get(index - 1)
+                    .or(separators.last())
+                    .map(|separator| separator.to_string())
+                {
+                    let separator = self.text(&separator, style.font_size, false);
+                    let width = separator.width + style.font_size * 3.0 / 18.0;
+                    inner.place(separator, x, 0.0);
+                    x += width;
+                }
+            }
+            let child = self.node(child, style);
+            let width = child.width;
+            inner.place(child, x, 0.0);
+            x += width;
+        }
+
+        let mut fenced = MathBox::default();
+        let open = self.fence(open, inner.ascent, inner.descent, style);
+        let close = self.fence(close, inner.ascent, inner.descent, style);
+        let open_width = open.width;
+        let inner_width = inner.width;
+        fenced.place(open, 0.0, 0.0);
+        fenced.place(inner, open_width, 0.0);
+        fenced.place(close, open_width + inner_width, 0.0);
+        fenced
+    }
+
+    fn text(&mut self, text: &str, font_size: f32, italic: bool) -> MathBox {
+        self.styled_text(text, font_size, italic, false)
+    }
+
+    fn styled_text(&mut self, text: &str, font_size: f32, italic: bool, bold: bool) -> MathBox {
+        let mut attrs = self.attrs.clone();
+        if italic {
+            attrs = attrs.style(Style::Italic);
+        }
+        if bold {
+            attrs = attrs.weight(Weight::BOLD);
+        }
+        let line_height = font_size * (ASCENT + DESCENT) * 1.2;
+        let mut buffer = Buffer::new(self.font_system, Metrics::new(font_size, line_height));
+        buffer.set_size(self.font_system, None, None);
+        buffer.set_rich_text(
+            self.font_system,
+            [(text, attrs.clone())],
+            &attrs,
+            Shaping::Advanced,
+            None,
+        );
+        buffer.shape_until_scroll(self.font_system, false);
+        let (width, baseline) = buffer
+            .layout_runs()
+            .next()
+            .map(|run| (run.line_w, run.line_y))
+            .unwrap_or((0.0, font_size));
+        // Leave some room for glyphs that go past their advance
+        buffer.set_size(
+            self.font_system,
+            Some(width + font_size * 0.5),
+            Some(line_height),
+        );
+
+        MathBox {
+            width,
+            ascent: font_size * ASCENT,
+            descent: font_size * DESCENT,
+            runs: vec![MathRun {
+                buffer,
+                pos: [0.0, -baseline],
+            }],
+            rules: Vec::new(),
+        }
+    }
+}
+
+fn token_text(node: Node) -> String {
+    node.descendants()
+        .filter(Node::is_text)
+        .filter_map(|text| text.text())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn is_fence(node: Node) -> bool {
+    if node.tag_name().name() != "mo" || node.attribute("stretchy") == Some("false") {
+        return false;
+    }
+    let text = token_text(node);
+    text.chars().count() == 1 && FENCES.contains(text.as_str())
+}
+
+fn is_big_operator(text: &str) -> bool {
+    (text.chars().count() == 1 && BIG_OPERATORS.contains(text))
+        || matches!(text, "lim" | "max" | "min" | "sup" | "inf")
+}
+
+fn is_big_operator_node(node: Node) -> bool {
+    node.tag_name().name() == "mo"
+        && is_big_operator(&token_text(node))
+        && node.attribute("movablelimits") != Some("false")
+}
+
+/// Whether a token's `mathvariant` is one of the
+/// bold ones, like `bold` or `bold-italic`
+fn is_bold(node: Node) -> bool {
+    node.attribute("mathvariant")
+        .is_some_and(|variant| variant.contains("bold"))
+}
+
+/// Parses lengths like `1em`, `0.5ex` or `3px`,
+/// or named spaces like `thin`
+fn parse_length(length: &str, font_size: f32) -> Option<f32> {
+    let length = length.trim();
+    match length {
+        "thin" | "thinmathspace" => return Some(font_size * 3.0 / 18.0),
+        "medium" | "mediummathspace" => return Some(font_size * 4.0 / 18.0),
+        "thick" | "thickmathspace" => return Some(font_size * 5.0 / 18.0),
+        _ => {}
+    }
+    let split = length
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(length.len());
+    let (number, unit) = length.split_at(split);
+    let number: f32 = number.parse().ok()?;
+    Some(match unit {
+        "em" => number * font_size,
+        "ex" => number * font_size * 0.5,
+        "px" | "" => number,
+        "pt" => number * 4.0 / 3.0,
+        "mu" => number * font_size / 18.0,
+        _ => return None,
+    })
+}
@@ -1,3 +1,13 @@
+#[cfg(feature = "layout")]
+use egui_glyphon::cosmic_text::{Attrs, FontSystem};
+
+pub mod latex;
+#[cfg(feature = "layout")]
+mod layout;
+
+#[cfg(feature = "layout")]
+pub use layout::{MathLayout, MathRule, MathRun};
+
 pub struct MathMLDoc<'a> {
     tree: roxmltree::Document<'a>,
 }
@@ -5,13 +15,37 @@ pub struct MathMLDoc<'a> {
 impl<'a> MathMLDoc<'a> {
     pub fn new(document: &'a str) -> Result<MathMLDoc<'a>, roxmltree::Error> {
         let tree = roxmltree::Document::parse(document)?;
-        if tree.root().tag_name().name() != "math" {
+        if tree.root_element().tag_name().name() != "math" {
             return Err(roxmltree::Error::InvalidName(
-                tree.text_pos_at(tree.root().range().start),
+                tree.text_pos_at(tree.root_element().range().start),
             ));
         }
         Ok(Self { tree })
     }
 
-    // pub fn resolve(&mut self, buffers: buffers) {}
+    /// Whether the formula is `<math display="block">`
+    pub fn is_block(&self) -> bool {
+        self.tree.root_element().attribute("display") == Some("block")
+    }
+
+    /// Lays the formula out into runs of text and lines, using
+    /// `attrs` for the font and color of the text. Display
+    /// formulas have bigger operators, and limits under and
+    /// over them instead of to their side
+    #[cfg(feature = "layout")]
+    pub fn resolve(
+        &self,
+        font_system: &mut FontSystem,
+        attrs: &Attrs,
+        font_size: f32,
+        display: bool,
+    ) -> MathLayout {
+        layout::layout(
+            self.tree.root_element(),
+            font_system,
+            attrs,
+            font_size,
+            display || self.is_block(),
+        )
+    }
 }
@@ -25,6 +25,7 @@ impl eframe::App for App {
     fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
         egui::CentralPanel::default().show(ctx, |ui| {
             let max_rect = ui.max_rect();
+            let mut clicked_link = None;
 
             ctx.input(|input| {
                 for event in input
@@ -37,9 +38,26 @@ impl eframe::App for App {
                         egui::Event::PointerButton {
                             pressed: true,
                             modifiers: Modifiers::NONE,
+                            pos,
                             ..
+                        } => {
+                            let link = self
+                                .shared_data
+                                .resolved
+                                .load()
+                                .deref()
+                                .as_ref()
+                                .and_then(|resolved| resolved.link_at(max_rect, *pos));
+                            if link.is_some() {
+                                clicked_link = link;
+                            } else {
+                                self.shared_data
+                                    .root_owner_sender
+                                    .send(FileOwnerMessage::Next(false))
+                                    .unwrap();
+                            }
                         }
-                        | egui::Event::Key {
+                        egui::Event::Key {
                             key: egui::Key::ArrowRight | egui::Key::Space,
                             pressed: true,
                             ..
@@ -124,6 +142,18 @@ impl eframe::App for App {
                 }
             });
 
+            // Outside of `ctx.input`, which can't open URLs while it holds the input
+            if let Some(link) = clicked_link {
+                if link.starts_with('#') {
+                    self.shared_data
+                        .root_owner_sender
+                        .send(FileOwnerMessage::Link(link))
+                        .unwrap();
+                } else {
+                    ctx.open_url(egui::OpenUrl::new_tab(link));
+                }
+            }
+
             let mut buffers = Vec::new();
 
             if let Some(resolved) = self.shared_data.resolved.load().deref() {
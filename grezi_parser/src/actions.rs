@@ -7,6 +7,8 @@ use smart_default::SmartDefault;
 
 #[derive(Deserialize, Serialize, SmartDefault, Debug, Clone)]
 pub struct SlideParams {
+    /// What links like `#intro` call this slide
+    pub name: Option<smartstring::alias::String>,
     pub speaker_notes: Option<Arc<str>>,
     pub stagger: f64,
     #[default = 0.5]
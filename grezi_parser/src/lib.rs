@@ -17,6 +17,7 @@ pub mod slide;
 pub mod text;
 
 pub const HIGHLIGHT_COLOR_DEFAULT: Color32 = Color32::from_rgba_premultiplied(61, 61, 53, 12);
+pub const LINK_COLOR_DEFAULT: Color32 = Color32::from_rgb(88, 166, 255);
 
 #[derive(Default, Serialize, Deserialize, Debug)]
 pub struct GrzRoot {
@@ -27,4 +28,27 @@ pub struct GrzRoot {
     >,
     pub objects: HashMap<smartstring::alias::String, Object, ahash::RandomState>,
     pub slides: IndexMap<u64, Slide, BuildHasherDefault<Passthru>>,
+    #[serde(skip)]
+    #[cfg(feature = "parse")]
+    // Only used while parsing text objects
+    pub styles: parse::slideshow::style::Styles,
+}
+
+impl GrzRoot {
+    /// The slide a link like `#intro` goes to, which is the
+    /// slide with that name, or else the slide with that
+    /// number counting from 1
+    pub fn slide_index(&self, link: &str) -> Option<usize> {
+        let target = link.strip_prefix('#')?;
+        self.slides
+            .values()
+            .position(|slide| slide.slide_params.name.as_deref() == Some(target))
+            .or_else(|| {
+                target
+                    .parse::<usize>()
+                    .ok()?
+                    .checked_sub(1)
+                    .filter(|index| *index < self.slides.len())
+            })
+    }
 }
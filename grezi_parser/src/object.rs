@@ -8,7 +8,7 @@ use url::Url;
 
 use crate::text::{Align, TextSection};
 
-#[derive(SmartDefault, Serialize, Deserialize, Debug)]
+#[derive(SmartDefault, Serialize, Deserialize, Debug, Clone)]
 pub struct Object {
     pub parameters: ObjInner,
     #[serde(skip)]
@@ -16,9 +16,13 @@ pub struct Object {
     // Used to remove objects that are no longer in the syntax tree
     #[default = true]
     pub present: bool,
+    #[serde(skip)]
+    #[cfg(feature = "parse")]
+    // Used to derive other objects from this one
+    pub declaration: crate::parse::slideshow::object::ObjDeclaration,
 }
 
-#[derive(SmartDefault, Serialize, Deserialize, derive_more::Debug)]
+#[derive(SmartDefault, Serialize, Deserialize, derive_more::Debug, Clone)]
 pub enum ObjInner {
     Text {
         job: SmallVec<[TextSection; 1]>,
@@ -332,6 +332,7 @@ impl<'a> GrzCursor<'a> {
     }
 }
 
+#[derive(Clone, Copy)]
 pub struct ErrorInfo<'a> {
     pub source: &'a Rope,
     pub tree: &'a Tree,
@@ -6,13 +6,12 @@ use parking_lot::{Mutex, MutexGuard};
 use tracing::instrument;
 use tree_sitter_highlight::{Highlight, HighlightConfiguration, HighlightEvent};
 
-use crate::parse::helix_loader;
+use crate::parse::helix_loader::{self, grammar::GrammarError};
 
 pub struct GrzHighlighter {
     pub source: Arc<str>,
     pub grz_config: HighlightConfiguration,
-    pub other_configs:
-        Mutex<HashMap<&'static str, Result<HighlightConfiguration, libloading::Error>>>,
+    pub other_configs: Mutex<HashMap<&'static str, Result<HighlightConfiguration, GrammarError>>>,
     pub highlighter: Mutex<tree_sitter_highlight::Highlighter>,
 }
 
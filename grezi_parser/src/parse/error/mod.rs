@@ -57,6 +57,14 @@ pub enum ParseError {
     NotFound(#[label("{1}")] super::CharRange, &'static str),
     #[error("Viewbox error")]
     Viewbox(#[label("{1}")] super::CharRange, String),
+    #[error("Image error")]
+    Image(#[label("{1}")] super::CharRange, String),
+    #[error("Missing argument")]
+    MissingArgument(#[label("{1}")] super::CharRange, String),
+    #[error("Include error")]
+    Include(#[label("{1}")] super::CharRange, String),
+    #[error("Theme error")]
+    Theme(#[label("{1}")] super::CharRange, String),
     #[error("Bad node")]
     BadNode(
         #[label("{2}; Found a `{1:?}` here")] super::CharRange,
@@ -67,6 +75,43 @@ pub enum ParseError {
     #[diagnostic(severity(Warning))]
     #[diagnostic(help("Try making this name under 23 characters so it can be inlined"))]
     LongName(#[label("This name is pretty long")] super::CharRange),
+    #[error("Unused object")]
+    #[diagnostic(severity(Warning), code(grezi::unused_object))]
+    UnusedObject(
+        #[label("`{1}` is never placed on a slide")] super::CharRange,
+        String,
+    ),
+    #[error("Unused viewbox")]
+    #[diagnostic(severity(Warning), code(grezi::unused_viewbox))]
+    UnusedViewbox(#[label("`{1}` is never used")] super::CharRange, String),
+    #[error("Object not on slide")]
+    #[diagnostic(severity(Warning), code(grezi::object_not_on_slide))]
+    ObjectNotOnSlide(
+        #[label("`{1}` is not on this slide, so this action does nothing")] super::CharRange,
+        String,
+    ),
+    #[error("Unknown language")]
+    #[diagnostic(
+        severity(Warning),
+        code(grezi::unknown_language),
+        help(
+            "Install the grammar for it in your Helix runtime directory, or build with the `bundled-grammars` feature"
+        )
+    )]
+    UnknownLanguage(
+        #[label("No grammar for `{1}` was found, so this code won't be highlighted")]
+        super::CharRange,
+        String,
+    ),
+    #[error("Math error")]
+    #[diagnostic(severity(Warning), code(grezi::math))]
+    Math(#[label("{1}")] super::CharRange, String),
+    #[error("Name collision")]
+    #[diagnostic(code(grezi::name_collision))]
+    NameCollision(
+        #[label("`{1}` is the name of both a style and an object")] super::CharRange,
+        String,
+    ),
 }
 
 impl ParseError {
@@ -78,8 +123,18 @@ impl ParseError {
             Self::Missing(range, _) => Some(range),
             Self::NotFound(range, _) => Some(range),
             Self::Viewbox(range, _) => Some(range),
+            Self::Image(range, _) => Some(range),
+            Self::MissingArgument(range, _) => Some(range),
+            Self::Include(range, _) => Some(range),
+            Self::Theme(range, _) => Some(range),
             Self::BadNode(range, _, _) => Some(range),
             Self::LongName(range) => Some(range),
+            Self::UnusedObject(range, _) => Some(range),
+            Self::UnusedViewbox(range, _) => Some(range),
+            Self::ObjectNotOnSlide(range, _) => Some(range),
+            Self::UnknownLanguage(range, _) => Some(range),
+            Self::Math(range, _) => Some(range),
+            Self::NameCollision(range, _) => Some(range),
         }
     }
 
@@ -91,8 +146,18 @@ impl ParseError {
             Self::Missing(range, _) => Some(range),
             Self::NotFound(range, _) => Some(range),
             Self::Viewbox(range, _) => Some(range),
+            Self::Image(range, _) => Some(range),
+            Self::MissingArgument(range, _) => Some(range),
+            Self::Include(range, _) => Some(range),
+            Self::Theme(range, _) => Some(range),
             Self::BadNode(range, _, _) => Some(range),
             Self::LongName(range) => Some(range),
+            Self::UnusedObject(range, _) => Some(range),
+            Self::UnusedViewbox(range, _) => Some(range),
+            Self::ObjectNotOnSlide(range, _) => Some(range),
+            Self::UnknownLanguage(range, _) => Some(range),
+            Self::Math(range, _) => Some(range),
+            Self::NameCollision(range, _) => Some(range),
         }
     }
 }
@@ -106,6 +171,8 @@ impl From<io::Error> for ParseError {
 #[derive(Default)]
 pub struct ErrsWithSource {
     pub errors: boxcar::Vec<(ParseError, SpanTrace)>,
+    /// The errors of every included file, along with its path
+    pub included: boxcar::Vec<(String, Arc<ErrsWithSource>)>,
     source_code_and_tree: OnceLock<(Rope, Tree)>,
 }
 
@@ -177,6 +244,13 @@ impl Debug for ErrsWithSource {
             }
         }
 
+        for (_, (path, errors)) in &self.included {
+            if errors.errors.count() > 0 || errors.included.count() > 0 {
+                writeln!(f, "In `{}`:", path)?;
+                write!(f, "{:?}", errors)?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -185,6 +259,7 @@ impl From<io::Error> for ErrsWithSource {
     fn from(value: io::Error) -> Self {
         Self {
             errors: boxcar::vec![(value.into(), SpanTrace::capture())],
+            included: boxcar::Vec::new(),
             source_code_and_tree: OnceLock::new(),
         }
     }
@@ -0,0 +1,57 @@
+//! Grammars compiled into the parser, used when Helix
+//! doesn't have a grammar for a language, or isn't
+//! installed at all
+
+use tree_sitter::Language;
+
+/// A grammar along with the queries that come with it
+pub struct BundledGrammar {
+    pub language: Language,
+    pub highlights: &'static str,
+    pub injections: &'static str,
+    pub locals: &'static str,
+}
+
+/// Gives the bundled grammar for `name`, which may be the
+/// Helix name of a language or a common alias of it
+pub fn bundled_grammar(name: &str) -> Option<BundledGrammar> {
+    Some(match name {
+        "rust" | "rs" => BundledGrammar {
+            language: tree_sitter_rust::LANGUAGE.into(),
+            highlights: tree_sitter_rust::HIGHLIGHTS_QUERY,
+            injections: tree_sitter_rust::INJECTIONS_QUERY,
+            locals: "",
+        },
+        "python" | "py" => BundledGrammar {
+            language: tree_sitter_python::LANGUAGE.into(),
+            highlights: tree_sitter_python::HIGHLIGHTS_QUERY,
+            injections: "",
+            locals: "",
+        },
+        "javascript" | "js" => BundledGrammar {
+            language: tree_sitter_javascript::LANGUAGE.into(),
+            highlights: tree_sitter_javascript::HIGHLIGHT_QUERY,
+            injections: tree_sitter_javascript::INJECTIONS_QUERY,
+            locals: tree_sitter_javascript::LOCALS_QUERY,
+        },
+        "c" | "h" => BundledGrammar {
+            language: tree_sitter_c::LANGUAGE.into(),
+            highlights: tree_sitter_c::HIGHLIGHT_QUERY,
+            injections: "",
+            locals: "",
+        },
+        "json" => BundledGrammar {
+            language: tree_sitter_json::LANGUAGE.into(),
+            highlights: tree_sitter_json::HIGHLIGHTS_QUERY,
+            injections: "",
+            locals: "",
+        },
+        "bash" | "sh" | "shell" => BundledGrammar {
+            language: tree_sitter_bash::LANGUAGE.into(),
+            highlights: tree_sitter_bash::HIGHLIGHT_QUERY,
+            injections: "",
+            locals: "",
+        },
+        _ => return None,
+    })
+}
@@ -1,5 +1,6 @@
 use std::path::PathBuf;
-use tree_sitter::Language;
+use thiserror::Error;
+use tree_sitter::{Language, QueryError};
 use tree_sitter_highlight::HighlightConfiguration;
 
 #[cfg(unix)]
@@ -8,6 +9,15 @@ const DYLIB_EXTENSION: &str = "so";
 #[cfg(windows)]
 const DYLIB_EXTENSION: &str = "dll";
 
+/// Why the highlighting for a language couldn't be built
+#[derive(Error, Debug)]
+pub enum GrammarError {
+    #[error(transparent)]
+    Library(#[from] libloading::Error),
+    #[error(transparent)]
+    Query(#[from] QueryError),
+}
+
 pub fn get_language(name: &str) -> Result<Language, libloading::Error> {
     use libloading::{Library, Symbol};
     let mut rel_library_path = PathBuf::new().join("grammars").join(name);
@@ -25,10 +35,33 @@ pub fn get_language(name: &str) -> Result<Language, libloading::Error> {
     Ok(language)
 }
 
-pub fn get_highlight_configuration(
-    name: &str,
-) -> Result<HighlightConfiguration, libloading::Error> {
-    let language = get_language(name)?;
+/// Builds the highlighting for `name` from the grammar and queries
+/// in the Helix runtime, falling back to the grammars bundled with
+/// the `bundled-grammars` feature when Helix doesn't have one
+///
+/// The runtime queries are written for the runtime grammar, so a
+/// bundled grammar is always used with its own queries
+pub fn get_highlight_configuration(name: &str) -> Result<HighlightConfiguration, GrammarError> {
+    let language = match get_language(name) {
+        Ok(language) => language,
+        #[cfg(feature = "bundled-grammars")]
+        Err(e) => {
+            // There's no grammar in the runtime, so use
+            // the bundled one if there is one
+            let Some(bundled) = super::bundled::bundled_grammar(name) else {
+                return Err(e.into());
+            };
+            return Ok(HighlightConfiguration::new(
+                bundled.language,
+                "language",
+                bundled.highlights,
+                bundled.injections,
+                bundled.locals,
+            )?);
+        }
+        #[cfg(not(feature = "bundled-grammars"))]
+        Err(e) => return Err(e.into()),
+    };
 
     Ok(HighlightConfiguration::new(
         language,
@@ -42,8 +75,7 @@ pub fn get_highlight_configuration(
         load_runtime_file(name, "locals.scm")
             .unwrap_or_default()
             .as_str(),
-    )
-    .unwrap())
+    )?)
 }
 
 /// Gives the contents of a file from a language's `runtime/queries/<lang>`
@@ -5,6 +5,8 @@
 //!
 //! It is borrowed from <https://github.com/helix-editor/helix>
 
+#[cfg(feature = "bundled-grammars")]
+pub mod bundled;
 pub mod grammar;
 pub mod path;
 
@@ -101,6 +103,17 @@ pub fn runtime_file(rel_path: &Path) -> PathBuf {
     })
 }
 
+/// Find a theme by name in the user's `themes/` directory,
+/// then in the runtime directories
+pub fn find_theme_file(name: &str) -> Option<PathBuf> {
+    let file = format!("{}.toml", name);
+    let user_theme = config_dir().join("themes").join(&file);
+    if user_theme.exists() {
+        return Some(user_theme);
+    }
+    find_runtime_file(&Path::new("themes").join(file))
+}
+
 pub fn config_dir() -> PathBuf {
     // TODO: allow env var override
     let strategy = choose_base_strategy().expect("Unable to find the config directory!");
@@ -13,7 +13,7 @@ use error::ErrsWithSource;
 use miette::SourceSpan;
 use prehash::Passthru;
 use ropey::Rope;
-use slideshow::viewbox::Viewbox;
+use slideshow::{include::Includes, registers::Registers, viewbox::Viewbox};
 use tracing::instrument;
 use tree_sitter::{InputEdit, Point, Tree};
 use tree_sitter_grz::NodeKind;
@@ -34,6 +34,7 @@ pub struct GrzFile {
     pub source: Rope,
     pub path_to_grz: String,
     pub slideshow: GrzRoot,
+    pub includes: Includes,
 }
 
 impl GrzFile {
@@ -77,6 +78,7 @@ impl GrzFile {
             parser,
             incremental_state: None,
             slideshow: GrzRoot::default(),
+            includes: Includes::default(),
         })
     }
 
@@ -93,9 +95,17 @@ impl GrzFile {
         self.error_free_tree = None;
         self.incremental_state = None;
         self.slideshow = GrzRoot::default();
+        self.includes.clear();
         self.parse(&[])
     }
 
+    /// Replaces the source of a file this one includes, or re-reads
+    /// it from disk if `source` is `None`. Returns whether this file
+    /// depends on it, and needs to be parsed again
+    pub fn reload_include(&mut self, path: &str, source: Option<&Rope>) -> bool {
+        self.includes.reload(path, source)
+    }
+
     pub fn find_slide_index_for_edit(
         &self,
         edit: &InputEdit,
@@ -225,6 +235,7 @@ impl GrzFile {
                 .goto_first_child(NodeKind::SymSourceFile)?
                 .ok_or_else(|| io::Error::new(ErrorKind::UnexpectedEof, "Source file is empty"))?,
             &self.path_to_grz,
+            &mut self.includes,
             Arc::clone(&errors),
         ) {
             Ok(incremental_state) => self.incremental_state = Some(incremental_state),
@@ -240,6 +251,9 @@ impl GrzFile {
 #[derive(Debug, Default)]
 pub struct IncrementalState {
     pub viewbox_nodes: HashMap<smartstring::alias::String, Viewbox>,
+    /// The registers as they were at the end of the file,
+    /// which are carried over into files including this one
+    pub registers: Registers,
 }
 
 #[derive(Debug, Clone)]
@@ -281,6 +295,32 @@ impl CharRange {
             byte_range: byte_range.start_byte..byte_range.end_byte,
         })
     }
+
+    /// The range of any bytes in the rope, even
+    /// ones that aren't the range of a node
+    pub fn from_byte_range(
+        byte_range: std::ops::Range<usize>,
+        current_rope: &Rope,
+    ) -> io::Result<CharRange> {
+        let char_pos = |byte: usize| -> io::Result<(usize, usize)> {
+            let line = current_rope
+                .try_byte_to_line(byte)
+                .map_err(|e| io::Error::new(ErrorKind::NotFound, e))?;
+            let char = current_rope
+                .try_byte_to_char(byte)
+                .map_err(|e| io::Error::new(ErrorKind::NotFound, e))?;
+            Ok((line, char - current_rope.line_to_char(line)))
+        };
+        let start = char_pos(byte_range.start)?;
+        let end = char_pos(byte_range.end)?;
+        Ok(CharRange {
+            start_line: start.0,
+            start_character: start.1,
+            end_line: end.0,
+            end_character: end.1,
+            byte_range,
+        })
+    }
 }
 
 pub fn char_pos_from_byte_pos(byte_pos: Point, current_rope: &Rope) -> io::Result<(usize, usize)> {
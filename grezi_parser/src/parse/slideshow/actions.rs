@@ -7,7 +7,6 @@ use tracing::instrument;
 use tree_sitter_grz::NodeKind;
 
 use crate::{
-    HIGHLIGHT_COLOR_DEFAULT,
     actions::{DrawableAction, SlideParams},
     parse::{
         cursor::{GrzCursor, GrzCursorGuard},
@@ -30,11 +29,135 @@ use super::{object::parse_color, slide::parse_alignment_from_chars};
 //     )
 // };
 
+/// The arguments an action takes, used for error messages
+/// and signature help
+#[derive(Debug)]
+pub struct ActionSignature {
+    pub name: &'static str,
+    /// The name of each argument, and what it does
+    pub parameters: &'static [(&'static str, &'static str)],
+    /// How many of the parameters have to be there
+    pub required: usize,
+    pub documentation: &'static str,
+}
+
+impl ActionSignature {
+    pub fn get(name: &str) -> Option<&'static ActionSignature> {
+        ACTION_SIGNATURES.iter().find(|s| s.name == name)
+    }
+
+    /// `line(from, from_alignment, to, to_alignment, [color])`,
+    /// where the parameters that aren't required are in brackets
+    pub fn label(&self) -> String {
+        let mut label = format!("{}(", self.name);
+        for (index, (parameter, _)) in self.parameters.iter().enumerate() {
+            if index != 0 {
+                label.push_str(", ");
+            }
+            if index < self.required {
+                label.push_str(parameter);
+            } else {
+                label.push('[');
+                label.push_str(parameter);
+                label.push(']');
+            }
+        }
+        label.push(')');
+        label
+    }
+
+    pub fn missing_argument(&self, index: usize) -> String {
+        match self.parameters.get(index) {
+            Some((parameter, _)) => format!(
+                "`{}` needs a `{}` argument here: `{}`",
+                self.name,
+                parameter,
+                self.label()
+            ),
+            None => format!(
+                "`{}` needs another argument here: `{}`",
+                self.name,
+                self.label()
+            ),
+        }
+    }
+}
+
+pub const ACTION_SIGNATURES: &[ActionSignature] = &[
+    ActionSignature {
+        name: "highlight",
+        parameters: &[
+            ("object", "The object to highlight"),
+            (
+                "start",
+                "`paragraph:line:offset` where the highlight starts, or `\"\"` for the whole object",
+            ),
+            ("end", "`paragraph:line:offset` where the highlight ends"),
+            ("color", "Color of the highlight"),
+        ],
+        required: 1,
+        documentation: "Highlights an object, or the text between the `paragraph:line:offset` locations `start` and `end`",
+    },
+    ActionSignature {
+        name: "line",
+        parameters: &[
+            ("from", "The object the line starts at"),
+            (
+                "from_alignment",
+                "Where on `from` the line starts, like `\"^^\"`",
+            ),
+            ("to", "The object the line ends at"),
+            ("to_alignment", "Where on `to` the line ends, like `\"__\"`"),
+            ("color", "Color of the line"),
+        ],
+        required: 4,
+        documentation: "Draws a line between two objects on the slide",
+    },
+    ActionSignature {
+        name: "name",
+        parameters: &[("name", "What links call the slide, like `intro`")],
+        required: 1,
+        documentation: "Names the slide, so text can link to it with `[text](#intro)`",
+    },
+    ActionSignature {
+        name: "speaker_notes",
+        parameters: &[("notes", "Djot text shown to the presenter")],
+        required: 1,
+        documentation: "Notes shown to the presenter alongside this slide",
+    },
+    ActionSignature {
+        name: "next",
+        parameters: &[(
+            "seconds",
+            "Defaults to advancing as soon as the slide is done",
+        )],
+        required: 0,
+        documentation: "Advances to the next slide after the given number of seconds",
+    },
+    ActionSignature {
+        name: "stagger",
+        parameters: &[("seconds", "Delay between each object")],
+        required: 1,
+        documentation: "Delay between the animations of each object on the slide",
+    },
+    ActionSignature {
+        name: "time",
+        parameters: &[("seconds", "Length of the animations")],
+        required: 1,
+        documentation: "How long the animations on the slide take",
+    },
+];
+
 macro_rules! goto_next_existing_sibling {
-    ($cursor:ident, $errors:ident) => {
+    ($cursor:ident, $errors:ident, $action:literal, $index:literal) => {
         if !$cursor.goto_next_sibling()? {
             $errors.append_error(
-                ParseError::NotFound($cursor.char_range()?, "Action needs more arguments"),
+                ParseError::MissingArgument(
+                    $cursor.char_range()?,
+                    ActionSignature::get($action)
+                        .map(|signature| signature.missing_argument($index))
+                        .unwrap_or_else(|| format!("`{}` needs another argument here", $action)),
+                ),
                 $cursor.error_info(),
             );
             return Ok(SmallVec::new());
@@ -77,6 +200,7 @@ impl SlideParams {
     pub fn parse(
         &mut self,
         mut cursor: GrzCursorGuard,
+        highlight_color: Color32,
         errors: Arc<ErrsWithSource>,
     ) -> io::Result<SmallVec<[DrawableAction; 2]>> {
         let mut drawable_actions = SmallVec::new();
@@ -85,7 +209,7 @@ impl SlideParams {
 
             match action {
                 x if x == "highlight" => {
-                    goto_next_existing_sibling!(action_cursor, errors);
+                    goto_next_existing_sibling!(action_cursor, errors, "highlight", 0);
                     let object_to_highlight = action_cursor.smartstring()?;
                     let mut locations = None;
                     if action_cursor.goto_next_sibling()? {
@@ -103,7 +227,7 @@ impl SlideParams {
                             });
                         }
                     }
-                    let mut color = HIGHLIGHT_COLOR_DEFAULT;
+                    let mut color = highlight_color;
                     if action_cursor.goto_next_sibling()? {
                         let color_str = action_cursor.node_to_string_literal()?;
                         let color_str: Cow<'_, str> = color_str.into();
@@ -121,13 +245,13 @@ impl SlideParams {
                     })
                 }
                 x if x == "line" => {
-                    goto_next_existing_sibling!(action_cursor, errors);
+                    goto_next_existing_sibling!(action_cursor, errors, "line", 0);
                     let first_object = action_cursor.smartstring()?;
-                    goto_next_existing_sibling!(action_cursor, errors);
+                    goto_next_existing_sibling!(action_cursor, errors, "line", 1);
                     let first_location = parse_alignment!(action_cursor, errors);
-                    goto_next_existing_sibling!(action_cursor, errors);
+                    goto_next_existing_sibling!(action_cursor, errors, "line", 2);
                     let second_object = action_cursor.smartstring()?;
-                    goto_next_existing_sibling!(action_cursor, errors);
+                    goto_next_existing_sibling!(action_cursor, errors, "line", 3);
                     let second_location = parse_alignment!(action_cursor, errors);
                     let mut color = Color32::WHITE;
                     if action_cursor.goto_next_sibling()? {
@@ -147,8 +271,13 @@ impl SlideParams {
                         color,
                     });
                 }
+                x if x == "name" => {
+                    goto_next_existing_sibling!(action_cursor, errors, "name", 0);
+                    let name: Cow<'_, str> = action_cursor.node_to_string_literal()?.into();
+                    self.name = Some(name.as_ref().into());
+                }
                 x if x == "speaker_notes" => {
-                    goto_next_existing_sibling!(action_cursor, errors);
+                    goto_next_existing_sibling!(action_cursor, errors, "speaker_notes", 0);
                     if let Some(notes_action_cursor) =
                         action_cursor.goto_first_child(NodeKind::SymStringLiteral)?
                     {
@@ -176,7 +305,7 @@ impl SlideParams {
                     }
                 }
                 x if x == "stagger" => {
-                    goto_next_existing_sibling!(action_cursor, errors);
+                    goto_next_existing_sibling!(action_cursor, errors, "stagger", 0);
                     let stagger_time: Cow<'_, str> = action_cursor.node_to_string_literal()?.into();
                     match stagger_time.parse() {
                         Ok(stagger_time) => self.stagger = stagger_time,
@@ -187,7 +316,7 @@ impl SlideParams {
                     }
                 }
                 x if x == "time" => {
-                    goto_next_existing_sibling!(action_cursor, errors);
+                    goto_next_existing_sibling!(action_cursor, errors, "time", 0);
                     let time_str: Cow<'_, str> = action_cursor.node_to_string_literal()?.into();
                     match time_str.parse() {
                         Ok(time) => self.time = time,
@@ -0,0 +1,213 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io,
+    path::Path,
+    sync::Arc,
+};
+
+use ropey::Rope;
+use tree_sitter_grz::{FieldName, NodeKind};
+
+use crate::{
+    GrzRoot,
+    parse::{
+        CharRange, GrzFile,
+        cursor::ErrorInfo,
+        error::{ErrsWithSource, ParseError},
+    },
+};
+
+use super::registers::Registers;
+
+/// A file spliced in with `<INCLUDE: "path.grz">`
+pub struct Include {
+    pub file: GrzFile,
+    /// `None` when the file changed and
+    /// needs to be parsed again
+    pub errors: Option<Arc<ErrsWithSource>>,
+}
+
+/// The files a `GrzFile` includes, keyed by their canonical path
+#[derive(Default)]
+pub struct Includes {
+    files: HashMap<String, Include>,
+    /// The files that are including this one, used
+    /// to catch files that include each other
+    stack: Vec<String>,
+}
+
+impl Includes {
+    pub fn get(&self, path: &str) -> Option<&Include> {
+        self.files.get(path)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Include)> {
+        self.files.iter()
+    }
+
+    pub fn is_included(&self) -> bool {
+        !self.stack.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+
+    /// Replaces the source of an included file, or re-reads it
+    /// from disk if `source` is `None`. Returns whether the file
+    /// is included, directly or not, and dependents need to be
+    /// parsed again
+    pub fn reload(&mut self, path: &str, source: Option<&Rope>) -> bool {
+        let mut depends = false;
+        self.files.retain(|include_path, include| {
+            if include_path == path {
+                depends = true;
+                let Some(source) = source else {
+                    return false;
+                };
+                include.file.source = source.clone();
+                include.errors = None;
+            } else if include.file.includes.reload(path, source) {
+                depends = true;
+                include.errors = None;
+            }
+            true
+        });
+        depends
+    }
+}
+
+/// Resolves an included path relative to the file including it
+pub fn resolve_include(path_to_grz: &str, path: &str) -> String {
+    canonical_path(
+        &Path::new(path_to_grz)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(path),
+    )
+}
+
+/// The key a file is included under. Falls back to the
+/// path as written if the file doesn't exist
+pub fn canonical_path(path: &Path) -> String {
+    path.canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+impl GrzRoot {
+    /// Splices the viewboxes, objects, styles and registers of another file
+    /// into this one. The registers are the ones the included file
+    /// ends with, and definitions in this file take precedence over
+    /// the included ones
+    #[allow(clippy::too_many_arguments)]
+    pub fn include(
+        &mut self,
+        path: &str,
+        range: CharRange,
+        path_to_grz: &str,
+        includes: &mut Includes,
+        registers: &mut Registers,
+        error_info: ErrorInfo,
+        errors: &ErrsWithSource,
+    ) -> io::Result<()> {
+        let resolved = resolve_include(path_to_grz, path);
+        let including = canonical_path(Path::new(path_to_grz));
+        if resolved == including || includes.stack.contains(&resolved) {
+            errors.append_error(
+                ParseError::Include(range, format!("`{}` ends up including itself", path)),
+                error_info,
+            );
+            return Ok(());
+        }
+
+        if !includes.files.contains_key(&resolved) {
+            match File::open(&resolved).and_then(|file| GrzFile::new(resolved.clone(), file)) {
+                Ok(file) => {
+                    includes
+                        .files
+                        .insert(resolved.clone(), Include { file, errors: None });
+                }
+                Err(e) => {
+                    errors.append_error(
+                        ParseError::Include(range, format!("Failed to read `{}`: {}", path, e)),
+                        error_info,
+                    );
+                    return Ok(());
+                }
+            }
+        }
+
+        let mut stack = includes.stack.clone();
+        stack.push(including);
+        let include = includes.files.get_mut(&resolved).unwrap();
+        let include_errors = match &include.errors {
+            Some(include_errors) => Arc::clone(include_errors),
+            None => {
+                include.file.includes.stack = stack;
+                include.file.clear_incremental_state();
+                let include_errors = include
+                    .file
+                    .parse(&[])
+                    .unwrap_or_else(|e| Arc::new(e.into()));
+                include.errors = Some(Arc::clone(&include_errors));
+                include_errors
+            }
+        };
+
+        if include_errors.has_errors() {
+            errors.append_error(
+                ParseError::Include(range, format!("`{}` has errors", path)),
+                error_info,
+            );
+        }
+        errors
+            .included
+            .push((resolved, Arc::clone(&include_errors)));
+
+        let local_definitions = local_definitions(error_info);
+        for (name, (split, _)) in include.file.slideshow.viewboxes.iter() {
+            if !local_definitions.contains(name.as_str()) {
+                self.viewboxes.insert(name.clone(), (split.clone(), true));
+            }
+        }
+        for (name, object) in include.file.slideshow.objects.iter() {
+            if !local_definitions.contains(name.as_str()) {
+                let mut object = object.clone();
+                object.present = true;
+                self.objects.insert(name.clone(), object);
+            }
+        }
+        for (name, style) in include.file.slideshow.styles.iter() {
+            if !local_definitions.contains(name.as_str()) {
+                let mut style = style.clone();
+                style.present = true;
+                self.styles.insert(name.clone(), style);
+            }
+        }
+        if let Some(incremental_state) = &include.file.incremental_state {
+            registers.include(&incremental_state.registers);
+        }
+
+        Ok(())
+    }
+}
+
+/// The names of the viewboxes and objects defined at the top level
+fn local_definitions(error_info: ErrorInfo) -> HashSet<String> {
+    let ErrorInfo { source, tree } = error_info;
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    root.named_children(&mut cursor)
+        .filter(|n| {
+            matches!(
+                NodeKind::from(n.kind_id()),
+                NodeKind::SymObj | NodeKind::SymViewbox
+            )
+        })
+        .filter_map(|n| n.child_by_field_id(FieldName::FieldName as u16))
+        .map(|name| source.byte_slice(name.byte_range()).to_string())
+        .collect()
+}
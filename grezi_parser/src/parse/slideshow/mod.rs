@@ -16,12 +16,18 @@ use viewbox::Viewbox;
 use crate::{GrzRoot, object::Object, parse::error::ErrsWithSource, slide::Slide};
 
 use super::{IncrementalState, cursor::GrzCursorGuard, error::ParseError};
+use include::Includes;
+use style::TextStyle;
 
 pub mod actions;
+pub mod include;
 pub mod object;
 pub mod registers;
 pub mod slide;
+pub mod style;
 pub mod text;
+pub mod theme;
+pub mod unused;
 pub mod viewbox;
 
 impl GrzRoot {
@@ -32,6 +38,7 @@ impl GrzRoot {
         damaged_node_map: HashSet<u64, BuildHasherDefault<Passthru>>,
         mut cursor: GrzCursorGuard,
         path_to_grz: &str,
+        includes: &mut Includes,
         errors: Arc<ErrsWithSource>,
     ) -> io::Result<IncrementalState> {
         if incremental_state.is_none() {
@@ -53,6 +60,10 @@ impl GrzRoot {
         for (_, object) in self.objects.iter_mut() {
             object.present = false;
         }
+        // If style isn't present, it gets deleted
+        for (_, style) in self.styles.iter_mut() {
+            style.present = false;
+        }
 
         let mut reused_slides = 0;
         let mut slide_time = Duration::from_secs(0);
@@ -69,9 +80,14 @@ impl GrzRoot {
                 NodeKind::SymSlide => {
                     let slide_instant = Instant::now();
                     let id = cursor.id(2, NodeKind::SymSlide)?;
-                    if let Some(mut slide) = self.slides.swap_remove(&id) {
+                    if let Some(mut slide) = self
+                        .slides
+                        .swap_remove(&id)
+                        .filter(|slide| !slide.theme_changed(&registers))
+                    {
                         reused_slides += 1;
                         slide.apply_registers(&registers);
+                        slide.check_actions(&cursor, &errors)?;
                         current_slides.insert(id, slide);
                         last_slide_changed = false;
                     } else if let Some(slide_tree_cursor) =
@@ -85,6 +101,7 @@ impl GrzRoot {
                             current_slides.last().map(|s| s.1).unwrap_or(&default_slide),
                             Arc::clone(&errors),
                         )?;
+                        slide.check_actions(&cursor, &errors)?;
                         current_slides.insert(id, slide);
                         last_slide_changed = true;
                     }
@@ -151,28 +168,65 @@ impl GrzRoot {
                 NodeKind::SymObj => {
                     let object_instant = Instant::now();
                     let id = cursor.node().id() as u64;
+                    let object_node = cursor.node();
                     if let Some(mut object_tree_cursor) =
                         cursor.goto_first_child(NodeKind::SymObj)?
                     {
                         let object_name = object_tree_cursor.smartstring()?;
+                        let name_range = object_tree_cursor.char_range()?;
                         object_tree_cursor.goto_next_sibling()?;
-                        if let Some(object) = self.objects.get_mut(&object_name) {
-                            if damaged_node_map.contains(&id) {
-                                *object = Object::default();
-                                object.apply_registers(&registers);
-                                object.parse(
-                                    object_tree_cursor,
-                                    path_to_grz,
-                                    Arc::clone(&errors),
-                                )?;
-                            } else {
-                                object.present = true;
-                                object.apply_registers(&registers);
+                        let is_style = style::declares_style(
+                            object_node,
+                            object_tree_cursor.error_info().source,
+                        );
+                        // Hover and goto definition look styles up
+                        // like objects, so they can't share a name
+                        let collides = if is_style {
+                            self.objects
+                                .get(&object_name)
+                                .is_some_and(|object| object.present)
+                        } else {
+                            self.styles
+                                .get(&object_name)
+                                .is_some_and(|style| style.present)
+                        };
+                        if collides {
+                            errors.append_error(
+                                ParseError::NameCollision(name_range, object_name.to_string()),
+                                object_tree_cursor.error_info(),
+                            );
+                        }
+                        if is_style {
+                            if let Some(style_cursor) =
+                                object_tree_cursor.goto_first_child(NodeKind::SymObjInner)?
+                            {
+                                let style = TextStyle::parse(style_cursor, Arc::clone(&errors))?;
+                                self.declare_style(object_name, style);
                             }
+                        } else if self.objects.get(&object_name).is_some_and(|object| {
+                            !damaged_node_map.contains(&id)
+                                && !object.dependencies_changed(
+                                    &self.objects,
+                                    &self.styles,
+                                    &registers,
+                                    path_to_grz,
+                                )
+                        }) {
+                            let object = self.objects.get_mut(&object_name).unwrap();
+                            object.present = true;
+                            object.apply_registers(&registers);
                         } else {
                             let mut object = Object::default();
                             object.apply_registers(&registers);
-                            object.parse(object_tree_cursor, path_to_grz, Arc::clone(&errors))?;
+                            object.parse(
+                                object_tree_cursor,
+                                &object_name,
+                                path_to_grz,
+                                &self.objects,
+                                &self.styles,
+                                &registers,
+                                Arc::clone(&errors),
+                            )?;
                             self.objects.insert(object_name, object);
                         }
                     }
@@ -183,7 +237,19 @@ impl GrzRoot {
                     if let Some(register_cursor) =
                         cursor.goto_first_child_raw(NodeKind::SymRegister)?
                     {
-                        registers.parse(register_cursor, Arc::clone(&errors))?;
+                        if let Some((path, range)) =
+                            registers.parse(register_cursor, path_to_grz, Arc::clone(&errors))?
+                        {
+                            self.include(
+                                &path,
+                                range,
+                                path_to_grz,
+                                includes,
+                                &mut registers,
+                                cursor.error_info(),
+                                &errors,
+                            )?;
+                        }
                     }
                     register_time += register_instant.elapsed();
                 }
@@ -191,7 +257,7 @@ impl GrzRoot {
                     let action_instant = Instant::now();
                     let id = cursor.id(1, NodeKind::SymActions)?;
                     if let Some(mut action) = self.slides.swap_remove(&id).and_then(|action| {
-                        if last_slide_changed {
+                        if last_slide_changed || action.theme_changed(&registers) {
                             None
                         } else {
                             Some(action)
@@ -199,6 +265,7 @@ impl GrzRoot {
                     }) {
                         reused_slides += 1;
                         action.apply_registers(&registers);
+                        action.check_actions(&cursor, &errors)?;
                         current_slides.insert(id, action);
                     } else if let Some(action_tree_cursor) =
                         cursor.goto_first_child(NodeKind::SymActions)?
@@ -211,13 +278,14 @@ impl GrzRoot {
                         let last_slide =
                             current_slides.last().map(|s| s.1).unwrap_or(&default_slide);
                         action.objects = last_slide.objects.clone();
+                        action.make_action();
                         action.parse(
                             action_tree_cursor,
                             &self.viewboxes,
                             last_slide,
                             Arc::clone(&errors),
                         )?;
-                        action.make_action();
+                        action.check_actions(&cursor, &errors)?;
                         action.actions.extend(last_slide.actions.iter().cloned());
                         current_slides.insert(id, action);
                     }
@@ -234,6 +302,12 @@ impl GrzRoot {
             }
         }
 
+        // Included files are mostly definitions
+        // that are used by the including file
+        if !includes.is_included() {
+            unused::report_unused_symbols(&cursor, &errors)?;
+        }
+
         tracing::warn!(
             ?slide_time,
             ?register_time,
@@ -245,8 +319,10 @@ impl GrzRoot {
         if !errors.has_errors() {
             self.objects.retain(|_, object| object.present);
             self.viewboxes.retain(|_, viewbox| viewbox.1);
+            self.styles.retain(|_, style| style.present);
             self.slides = current_slides;
         }
+        incremental_state.registers = registers;
 
         Ok(incremental_state)
     }
@@ -1,8 +1,21 @@
-use std::{borrow::Cow, io, num::NonZeroU16, ops::DerefMut, str::FromStr, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    io,
+    num::NonZeroU16,
+    ops::{DerefMut, Range},
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
 use css_color::Srgb;
 use ecolor::Color32;
-use ropey::RopeSlice;
+use ropey::{Rope, RopeSlice};
 use tracing::instrument;
 use tree_sitter_grz::{FieldName, NodeKind};
 use url::Url;
@@ -14,26 +27,95 @@ use crate::{
         cursor::{ErrorInfo, GrzCursor, GrzCursorGuard},
         error::{ErrsWithSource, ParseError},
     },
-    text::Align,
+    text::{Align, Family},
 };
 
 use super::{
     registers::Registers,
-    text::{StringLiteral, TextJobParams},
+    style::{Styles, styles_fingerprint},
+    text::{
+        CodeParams, StringLiteral, TextErrorKind, TextJobParams, code_theme::load_code_theme,
+        language_available, parse_line_ranges,
+    },
 };
 
+pub type Objects = HashMap<smartstring::alias::String, Object, ahash::RandomState>;
+
+/// Parameters that can be inherited by deriving from an object.
+/// Any other key is ignored by `ObjInner::parse` anyway
+const PARAMETER_NAMES: &[&str] = &[
+    "value",
+    "code",
+    "language",
+    "code_theme",
+    "align",
+    "color",
+    "font_family",
+    "font_size",
+    "style",
+    "tagged",
+    "line_height",
+    "line_numbers",
+    "start_line",
+    "highlight_lines",
+    "diff",
+    "tint",
+    "scale",
+    "stroke",
+    "height",
+];
+
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+pub(crate) fn next_generation() -> u64 {
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// The type and parameters an object was declared with, so
+/// that other objects can derive from it, like
+/// `Subtitle2: Subtitle(value: "...")`
+#[derive(Debug, Default, Clone)]
+pub struct ObjDeclaration {
+    /// The built in type this object ends up being
+    pub ty: smartstring::alias::String,
+    /// Every parameter, including the inherited ones, in order
+    pub params: Vec<(Option<&'static str>, String)>,
+    /// The objects this one derives from, nearest first
+    pub bases: Vec<smartstring::alias::String>,
+    /// The generation of the nearest base when this object was parsed
+    pub base_generation: u64,
+    /// Changes every time this object is parsed
+    pub generation: u64,
+    /// The `text_dependencies` a text object was parsed with
+    pub text_dependencies: Option<u64>,
+}
+
 impl Object {
     /// Parse name before entering this function
     #[instrument(skip_all, fields(source = %cursor.parent_source()?, type = std::any::type_name::<Self>()))]
+    #[allow(clippy::too_many_arguments)]
     pub fn parse(
         &mut self,
         mut cursor: GrzCursorGuard,
+        name: &str,
         path_to_grz: &str,
+        objects: &Objects,
+        styles: &Styles,
+        registers: &Registers,
         errors: Arc<ErrsWithSource>,
     ) -> io::Result<()> {
+        self.declaration.generation = next_generation();
         if let Some(obj_inner_cursor) = cursor.goto_first_child(NodeKind::SymObjInner)? {
-            self.parameters
-                .parse(obj_inner_cursor, path_to_grz, Arc::clone(&errors))?;
+            self.parameters.parse(
+                obj_inner_cursor,
+                name,
+                path_to_grz,
+                objects,
+                styles,
+                registers,
+                &mut self.declaration,
+                Arc::clone(&errors),
+            )?;
         } else {
             errors.append_error(
                 ParseError::Missing(cursor.char_range()?, "Missing inner details of object"),
@@ -45,19 +127,112 @@ impl Object {
     }
 
     pub fn apply_registers(&mut self, _registers: &Registers) {}
+
+    /// Whether the object this one derives from, or the styles and
+    /// themes a text object could use, were parsed again, or went
+    /// away, since this object was parsed
+    pub fn dependencies_changed(
+        &self,
+        objects: &Objects,
+        styles: &Styles,
+        registers: &Registers,
+        path_to_grz: &str,
+    ) -> bool {
+        let base_changed = self.declaration.bases.first().is_some_and(|base| {
+            objects
+                .get(base)
+                .filter(|base| base.present)
+                .map(|base| base.declaration.generation != self.declaration.base_generation)
+                .unwrap_or(true)
+        });
+        base_changed
+            || self
+                .declaration
+                .text_dependencies
+                .is_some_and(|dependencies| {
+                    let code_theme = self
+                        .declaration
+                        .params
+                        .iter()
+                        .find(|(key, _)| *key == Some("code_theme"))
+                        .and_then(|(_, name)| load_code_theme(name, path_to_grz).ok())
+                        .map(|(_, generation)| generation);
+                    dependencies != text_dependencies(styles, registers, code_theme)
+                })
+    }
+}
+
+/// Identifies the styles declared so far, the theme,
+/// and the generation of the object's `code_theme`
+fn text_dependencies(styles: &Styles, registers: &Registers, code_theme: Option<u64>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    styles_fingerprint(styles).hash(&mut hasher);
+    registers.theme.generation.hash(&mut hasher);
+    code_theme.hash(&mut hasher);
+    hasher.finish()
 }
 
 impl ObjInner {
     #[instrument(skip_all, fields(source = %cursor.parent_source()?, type = std::any::type_name::<Self>()))]
+    #[allow(clippy::too_many_arguments)]
     pub fn parse(
         &mut self,
         mut cursor: GrzCursorGuard,
+        name: &str,
         path_to_grz: &str,
+        objects: &Objects,
+        styles: &Styles,
+        registers: &Registers,
+        declaration: &mut ObjDeclaration,
         errors: Arc<ErrsWithSource>,
     ) -> io::Result<()> {
         let obj_type = cursor.rope_slice()?;
-        let mut obj_params = ObjParamParser::new(cursor.deref_mut())?;
-        match obj_type {
+        let obj_type_text: Cow<'_, str> = obj_type.into();
+        let mut inherited = Vec::new();
+        let base_range = cursor.char_range()?;
+        declaration.bases.clear();
+        declaration.text_dependencies = None;
+        declaration.ty = obj_type_text.as_ref().into();
+        if !matches!(
+            obj_type_text.as_ref(),
+            "Rect" | "Image" | "Header" | "Paragraph"
+        ) {
+            match objects.get(obj_type_text.as_ref()) {
+                Some(_) if derives_from(objects, obj_type_text.as_ref(), name) => {
+                    errors.append_error(
+                        ParseError::Syntax(base_range, "An object can't derive from itself"),
+                        cursor.error_info(),
+                    );
+                    return Ok(());
+                }
+                // Objects can only derive from objects declared before them
+                Some(base) if base.present => {
+                    inherited = base.declaration.params.clone();
+                    declaration.ty = base.declaration.ty.clone();
+                    declaration.bases.push(obj_type_text.as_ref().into());
+                    declaration
+                        .bases
+                        .extend(base.declaration.bases.iter().cloned());
+                    declaration.base_generation = base.declaration.generation;
+                }
+                _ => {
+                    errors.append_error(
+                        ParseError::NotFound(
+                            cursor.char_range()?,
+                            "That object type does not exist",
+                        ),
+                        cursor.error_info(),
+                    );
+                    return Ok(());
+                }
+            }
+        }
+        let mut obj_params = ObjParamParser::new(cursor.deref_mut())?.with_declaration(
+            inherited,
+            base_range,
+            &mut declaration.params,
+        );
+        match declaration.ty.as_str() {
             x if x == "Rect" => {
                 let mut color = Color32::WHITE;
                 let mut stroke = Color32::TRANSPARENT;
@@ -112,6 +287,7 @@ impl ObjInner {
                 let mut scale = None;
                 let mut url = Url::parse("file:/bruh/moment").unwrap();
                 let mut bytes = None;
+                let mut has_value = false;
                 while let Some(param) = obj_params.next() {
                     let param = param?;
 
@@ -139,6 +315,7 @@ impl ObjInner {
                             }
                         }
                         x if x.map(|x| x == "value").unwrap_or_default() || x.is_none() => {
+                            has_value = true;
                             let url_str: Cow<'_, str> = param.1.into();
                             match dunce::canonicalize(path_to_grz)
                                 .or(Err(()))
@@ -146,13 +323,24 @@ impl ObjInner {
                                 .and_then(|u| u.join(url_str.as_ref()).or(Err(())))
                             {
                                 Ok(u) => url = u,
-                                Err(_) => errors.append_error(
-                                    ParseError::Syntax(obj_params.char_range(), "Not a valid URL"),
-                                    obj_params.error_info(),
-                                ),
+                                Err(_) => {
+                                    errors.append_error(
+                                        ParseError::Syntax(
+                                            obj_params.char_range(),
+                                            "Not a valid URL",
+                                        ),
+                                        obj_params.error_info(),
+                                    );
+                                    continue;
+                                }
                             }
 
-                            bytes = std::fs::read(url.path()).ok().map(Arc::<[u8]>::from)
+                            bytes = read_image(
+                                &url,
+                                obj_params.char_range(),
+                                obj_params.error_info(),
+                                Arc::clone(&errors),
+                            );
                         }
                         _ => {}
                     }
@@ -165,26 +353,42 @@ impl ObjInner {
                         tint,
                         data,
                     };
-                } else {
+                } else if !has_value {
                     errors.append_error(
                         ParseError::NotFound(
                             cursor.char_range()?,
-                            "Could not find value parameter, or image specified in value parameter",
+                            "Could not find value parameter",
                         ),
                         cursor.error_info(),
                     );
                 }
             }
-            x if x == "Header" || x == "Paragraph" => {
+            x if x == "Header" || x == "Paragraph" || x == "Code" => {
+                declaration.text_dependencies = Some(text_dependencies(styles, registers, None));
+                let mut code = (x == "Code").then(CodeParams::default);
+                let mut style = None;
                 let mut line_height = None;
-                let mut align = Align::Left;
+                let mut align = None;
+                let mut color = None;
+                let mut font_family = None;
+                let mut font_size = None;
+                // Where the value is, and whether it's written
+                // the same as in the file, without escapes
+                let mut value_range = None;
                 let mut text_job_params = TextJobParams {
+                    default_attrs: registers.theme.default_attrs.clone(),
                     default_font_size: match x {
-                        x if x == "Header" => 64.0,
-                        _ => 48.0,
+                        x if x == "Header" => registers.theme.header_size,
+                        _ => registers.theme.paragraph_size,
                     },
+                    styles: Some(styles),
+                    theme: Arc::clone(&registers.theme),
+                    code_theme: registers.theme.code.clone(),
                     ..Default::default()
                 };
+                if code.is_some() {
+                    text_job_params.default_attrs.family = Family::Monospace;
+                }
 
                 while let Some(param) = obj_params.next() {
                     let param = param?;
@@ -194,47 +398,89 @@ impl ObjInner {
                             || x.map(|x| x == "code").unwrap_or_default()
                             || x.is_none() =>
                         {
+                            value_range = Some((
+                                obj_params.char_range(),
+                                matches!(param.1, StringLiteral::FullContent(_)),
+                            ));
                             text_job_params.value = param.1
                         }
                         x if x.map(|x| x == "language").unwrap_or_default() => {
+                            let language: Cow<'_, str> = param.1.clone().into();
+                            if !language.is_empty() && !language_available(language.as_ref()) {
+                                errors.append_error(
+                                    ParseError::UnknownLanguage(
+                                        obj_params.char_range(),
+                                        language.into_owned(),
+                                    ),
+                                    obj_params.error_info(),
+                                );
+                            }
                             text_job_params.language = param.1
                         }
-                        x if x.map(|x| x == "align").unwrap_or_default() => {
-                            align = match param.1.as_rope_slice() {
-                                x if x == "left" => Align::Left,
-                                x if x == "center" => Align::Center,
-                                x if x == "right" => Align::Right,
-                                x if x == "justified" => Align::Justified,
-                                x if x == "end" => Align::End,
-                                _ => {
-                                    errors.append_error(
-                                        ParseError::NotFound(
-                                            obj_params.char_range(),
-                                            "That alignment does not exist",
-                                        ),
-                                        obj_params.error_info(),
-                                    );
-                                    Align::Left
+                        x if x.map(|x| x == "code_theme").unwrap_or_default() => {
+                            let code_theme: Cow<'_, str> = param.1.into();
+                            match load_code_theme(code_theme.as_ref(), path_to_grz) {
+                                Ok((code_theme, generation)) => {
+                                    text_job_params.code_theme = code_theme;
+                                    // Edits to the theme's files style the object again
+                                    declaration.text_dependencies = Some(text_dependencies(
+                                        styles,
+                                        registers,
+                                        Some(generation),
+                                    ));
                                 }
+                                Err(e) => errors.append_error(
+                                    ParseError::Theme(
+                                        obj_params.char_range(),
+                                        format!(
+                                            "Failed to load code theme `{}`: {}",
+                                            code_theme, e
+                                        ),
+                                    ),
+                                    obj_params.error_info(),
+                                ),
+                            }
+                        }
+                        x if x.map(|x| x == "style").unwrap_or_default() => {
+                            let style_name: Cow<'_, str> = param.1.into();
+                            match styles
+                                .get(style_name.as_ref())
+                                .filter(|style| style.present)
+                            {
+                                Some(named) => style = Some(&named.style),
+                                None => errors.append_error(
+                                    ParseError::NotFound(
+                                        obj_params.char_range(),
+                                        "That style does not exist",
+                                    ),
+                                    obj_params.error_info(),
+                                ),
                             }
                         }
+                        x if x.map(|x| x == "align").unwrap_or_default() => {
+                            align = Some(parse_align(
+                                param.1.as_rope_slice(),
+                                obj_params.char_range(),
+                                obj_params.error_info(),
+                                Arc::clone(&errors),
+                            ));
+                        }
                         x if x.map(|x| x == "color").unwrap_or_default() => {
                             let color_str: Cow<'_, str> = param.1.into();
-                            text_job_params.default_attrs.color = parse_color(
+                            color = Some(parse_color(
                                 color_str.as_ref(),
                                 obj_params.char_range(),
                                 obj_params.error_info(),
                                 Arc::clone(&errors),
-                            )?;
+                            )?);
                         }
                         x if x.map(|x| x == "font_family").unwrap_or_default() => {
-                            let family: Cow<'_, str> = param.1.into();
-                            text_job_params.default_attrs.apply_fontstr(family.as_ref());
+                            font_family = Some(param.1);
                         }
                         x if x.map(|x| x == "font_size").unwrap_or_default() => {
                             let font_size_str: Cow<'_, str> = param.1.into();
                             match font_size_str.parse() {
-                                Ok(c) => text_job_params.default_font_size = c,
+                                Ok(c) => font_size = Some(c),
                                 Err(_) => errors.append_error(
                                     ParseError::Syntax(
                                         obj_params.char_range(),
@@ -282,14 +528,113 @@ impl ObjInner {
                                 ),
                             }
                         }
+                        x if x.map(|x| x == "line_numbers").unwrap_or_default() => {
+                            let line_numbers_str: Cow<'_, str> = param.1.into();
+                            match (&mut code, line_numbers_str.parse()) {
+                                (Some(code), Ok(c)) => code.line_numbers = c,
+                                (None, _) => {}
+                                (_, Err(_)) => errors.append_error(
+                                    ParseError::Syntax(
+                                        obj_params.char_range(),
+                                        "Not a valid boolean",
+                                    ),
+                                    obj_params.error_info(),
+                                ),
+                            }
+                        }
+                        x if x.map(|x| x == "start_line").unwrap_or_default() => {
+                            let start_line_str: Cow<'_, str> = param.1.into();
+                            match (&mut code, start_line_str.parse()) {
+                                (Some(code), Ok(c)) => code.start_line = c,
+                                (None, _) => {}
+                                (_, Err(_)) => errors.append_error(
+                                    ParseError::Syntax(
+                                        obj_params.char_range(),
+                                        "Not a valid line number",
+                                    ),
+                                    obj_params.error_info(),
+                                ),
+                            }
+                        }
+                        x if x.map(|x| x == "highlight_lines").unwrap_or_default() => {
+                            let ranges_str: Cow<'_, str> = param.1.into();
+                            match (&mut code, parse_line_ranges(ranges_str.as_ref())) {
+                                (Some(code), Some(ranges)) => code.highlight_lines = ranges,
+                                (None, _) => {}
+                                (_, None) => errors.append_error(
+                                    ParseError::Syntax(
+                                        obj_params.char_range(),
+                                        "Not a valid list of lines, like `2-4, 7`",
+                                    ),
+                                    obj_params.error_info(),
+                                ),
+                            }
+                        }
+                        x if x.map(|x| x == "diff").unwrap_or_default() => {
+                            let diff_str: Cow<'_, str> = param.1.into();
+                            match (&mut code, diff_str.parse()) {
+                                (Some(code), Ok(c)) => code.diff = c,
+                                (None, _) => {}
+                                (_, Err(_)) => errors.append_error(
+                                    ParseError::Syntax(
+                                        obj_params.char_range(),
+                                        "Not a valid boolean",
+                                    ),
+                                    obj_params.error_info(),
+                                ),
+                            }
+                        }
                         _ => {}
                     }
                 }
 
+                // The style goes first, so that the parameters
+                // of the object override it wherever they are
+                if let Some(style) = style {
+                    style.apply(&mut text_job_params.default_attrs);
+                    if let Some(font_size) = style.font_size {
+                        text_job_params.default_font_size = font_size;
+                    }
+                    line_height = line_height.or(style.line_height);
+                    align = align.or_else(|| style.align.clone());
+                }
+                if let Some(font_family) = font_family {
+                    let family: Cow<'_, str> = font_family.into();
+                    text_job_params.default_attrs.apply_fontstr(family.as_ref());
+                }
+                if let Some(color) = color {
+                    text_job_params.default_attrs.color = color;
+                }
+                if let Some(font_size) = font_size {
+                    text_job_params.default_font_size = font_size;
+                }
+
+                text_job_params.code = code;
+                let (job, text_errors) = text_job_params.format();
+                if let Some((range, exact)) = value_range {
+                    for error in text_errors {
+                        let range = match error.span {
+                            Some(span) if exact => {
+                                string_content_range(&range, span, obj_params.error_info().source)
+                                    .unwrap_or_else(|_| range.clone())
+                            }
+                            _ => range.clone(),
+                        };
+                        errors.append_error(
+                            match error.kind {
+                                TextErrorKind::Math(message) => ParseError::Math(range, message),
+                                TextErrorKind::UnknownLanguage(language) => {
+                                    ParseError::UnknownLanguage(range, language)
+                                }
+                            },
+                            obj_params.error_info(),
+                        );
+                    }
+                }
                 *self = Self::Text {
-                    job: text_job_params.into(),
+                    job,
                     line_height,
-                    align,
+                    align: align.unwrap_or_default(),
                 }
             }
             _ => errors.append_error(
@@ -301,21 +646,168 @@ impl ObjInner {
     }
 }
 
+/// Parses the alignment of text
+pub fn parse_align(
+    align: RopeSlice,
+    range: CharRange,
+    error_info: ErrorInfo,
+    errors: Arc<ErrsWithSource>,
+) -> Align {
+    match align {
+        x if x == "left" => Align::Left,
+        x if x == "center" => Align::Center,
+        x if x == "right" => Align::Right,
+        x if x == "justified" => Align::Justified,
+        x if x == "end" => Align::End,
+        _ => {
+            errors.append_error(
+                ParseError::NotFound(range, "That alignment does not exist"),
+                error_info,
+            );
+            Align::Left
+        }
+    }
+}
+
+/// The range of `span` in the text of the string literal
+/// at `range`, which starts after its opening quote
+fn string_content_range(
+    range: &CharRange,
+    span: Range<usize>,
+    source: &Rope,
+) -> io::Result<CharRange> {
+    let opening: String = source
+        .byte_slice(range.byte_range.clone())
+        .chars()
+        .take(3)
+        .collect();
+    let quote_len = if opening.starts_with("r#\"") {
+        3
+    } else if opening.starts_with("b\"") {
+        2
+    } else if opening.starts_with('"') {
+        1
+    } else {
+        0
+    };
+    let start = range.byte_range.start + quote_len;
+    CharRange::from_byte_range(start + span.start..start + span.end, source)
+}
+
+/// Whether `base`, or anything it derives from, is `name`. The
+/// chain is followed through `objects` as they are now, remembering
+/// the bases visited so that a cycle that's already there ends it
+fn derives_from(objects: &Objects, base: &str, name: &str) -> bool {
+    let mut visited: Vec<&str> = Vec::new();
+    let mut base = base;
+    loop {
+        if base == name {
+            return true;
+        }
+        if visited.contains(&base) {
+            return false;
+        }
+        visited.push(base);
+        match objects
+            .get(base)
+            .and_then(|object| object.declaration.bases.first())
+        {
+            Some(next) => base = next.as_str(),
+            None => return false,
+        }
+    }
+}
+
+/// Reads the image at `url`, reporting whether it's missing,
+/// unreadable, or not in a format that can be decoded
+fn read_image(
+    url: &Url,
+    range: CharRange,
+    error_info: ErrorInfo,
+    errors: Arc<ErrsWithSource>,
+) -> Option<Arc<[u8]>> {
+    let path = url
+        .to_file_path()
+        .unwrap_or_else(|_| PathBuf::from(url.path()));
+    let error = match std::fs::read(&path) {
+        Ok(data) => {
+            // Reading the header catches truncated files along with
+            // formats that there's no decoder for
+            let header = image::ImageReader::new(io::Cursor::new(&data))
+                .with_guessed_format()
+                .map_err(image::ImageError::IoError)
+                .and_then(|reader| reader.into_dimensions());
+            match header {
+                Ok(_) => return Some(data.into()),
+                Err(e) => format!("`{}` could not be decoded: {}", path.display(), e),
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            format!("`{}` does not exist", path.display())
+        }
+        Err(e) => format!("`{}` could not be read: {}", path.display(), e),
+    };
+    errors.append_error(ParseError::Image(range, error), error_info);
+    None
+}
+
 pub struct ObjParamParser<'a, 'b> {
     cursor: &'b mut GrzCursor<'a>,
     char_range: CharRange,
     span: Option<tracing::span::EnteredSpan>,
+    /// Parameters of the object being derived from. These come
+    /// first, so that they can be overridden
+    inherited: std::vec::IntoIter<(Option<&'static str>, String)>,
+    /// Where the object being derived from is named, which is
+    /// where mistakes in the inherited parameters are shown
+    base_range: CharRange,
+    /// Every parameter that was parsed, for `ObjDeclaration`
+    declared: Option<&'b mut Vec<(Option<&'static str>, String)>>,
 }
 
 impl<'a, 'b> ObjParamParser<'a, 'b> {
     pub fn new(cursor: &'b mut GrzCursor<'a>) -> io::Result<ObjParamParser<'a, 'b>> {
+        let char_range = cursor.char_range()?;
         Ok(Self {
-            char_range: cursor.char_range()?,
+            base_range: char_range.clone(),
+            char_range,
             cursor,
             span: None,
+            inherited: Vec::new().into_iter(),
+            declared: None,
         })
     }
 
+    /// Yields `inherited` before the parameters in the tree,
+    /// and records all of them into `declared`
+    pub fn with_declaration(
+        mut self,
+        inherited: Vec<(Option<&'static str>, String)>,
+        base_range: CharRange,
+        declared: &'b mut Vec<(Option<&'static str>, String)>,
+    ) -> Self {
+        declared.clear();
+        self.inherited = inherited.into_iter();
+        self.base_range = base_range;
+        self.declared = Some(declared);
+        self
+    }
+
+    fn declare(&mut self, key: Option<RopeSlice>, value: &StringLiteral) {
+        let Some(declared) = self.declared.as_mut() else {
+            return;
+        };
+        let key = match key {
+            Some(key) => match PARAMETER_NAMES.iter().find(|name| key == **name) {
+                Some(name) => Some(*name),
+                None => return,
+            },
+            None => None,
+        };
+        declared.retain(|(k, _)| *k != key);
+        declared.push((key, Cow::from(value.clone()).into_owned()));
+    }
+
     pub fn char_range(&self) -> CharRange {
         self.char_range.clone()
     }
@@ -330,6 +822,12 @@ impl<'a> Iterator for ObjParamParser<'a, '_> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.span = None;
+        if let Some((key, value)) = self.inherited.next() {
+            let value = StringLiteral::Escaped(value);
+            self.char_range = self.base_range.clone();
+            self.declare(key.map(RopeSlice::from), &value);
+            return Some(Ok((key.map(RopeSlice::from), value)));
+        }
         match self.cursor.goto_next_sibling() {
             Ok(result) => {
                 if !result {
@@ -349,6 +847,7 @@ impl<'a> Iterator for ObjParamParser<'a, '_> {
             Ok(c) => match parse_obj_param(c?) {
                 Ok(params) => {
                     self.char_range = params.0;
+                    self.declare(params.1.0, &params.1.1);
                     Some(Ok(params.1))
                 }
                 Err(e) => Some(Err(e)),
@@ -1,20 +1,26 @@
-use std::{borrow::Cow, io, sync::Arc};
+use std::{borrow::Cow, io, path::Path, sync::Arc};
 
+use css_color::Srgb;
 use grezi_layout::Flex;
 use smart_default::SmartDefault;
 use tracing::instrument;
 
 use crate::{
     parse::{
+        CharRange,
         cursor::GrzCursorGuardRaw,
         error::{ErrsWithSource, ParseError},
     },
     slide::BgColor,
 };
 
-use super::object::{ObjParamParser, parse_color_raw};
+use super::{
+    include::resolve_include,
+    object::{ObjParamParser, parse_color_raw},
+    theme::{Theme, load_theme},
+};
 
-#[derive(SmartDefault)]
+#[derive(SmartDefault, Debug, Clone)]
 pub struct Registers {
     #[default = 15.0]
     pub margin: f32,
@@ -23,15 +29,61 @@ pub struct Registers {
     pub flex: Flex,
     pub bg: BgColor,
     pub create_edges: bool,
+    pub theme: Arc<Theme>,
+    /// Which registers were set in the file
+    pub set: SetRegisters,
+}
+
+/// Which registers a file sets, so that files including
+/// it only take those and keep the rest of their own
+#[derive(Default, Debug, Clone, Copy)]
+pub struct SetRegisters {
+    pub margin: bool,
+    pub margin_per: bool,
+    pub flex: bool,
+    pub bg: bool,
+    pub create_edges: bool,
+    pub theme: bool,
 }
 
 impl Registers {
+    /// Takes the registers an included file set
+    pub fn include(&mut self, included: &Registers) {
+        if included.set.margin {
+            self.margin = included.margin;
+            self.set.margin = true;
+        }
+        if included.set.margin_per {
+            self.margin_per = included.margin_per;
+            self.set.margin_per = true;
+        }
+        if included.set.flex {
+            self.flex = included.flex;
+            self.set.flex = true;
+        }
+        if included.set.bg {
+            self.bg = included.bg;
+            self.set.bg = true;
+        }
+        if included.set.create_edges {
+            self.create_edges = included.create_edges;
+            self.set.create_edges = true;
+        }
+        if included.set.theme {
+            self.theme = Arc::clone(&included.theme);
+            self.set.theme = true;
+        }
+    }
+
+    /// Returns the path of an `INCLUDE` register, which
+    /// is spliced in by `GrzRoot::include`
     #[instrument(skip_all, fields(source = %cursor.parent_source()?, type = std::any::type_name::<Self>()))]
     pub fn parse(
         &mut self,
         mut cursor: GrzCursorGuardRaw,
+        path_to_grz: &str,
         errors: Arc<ErrsWithSource>,
-    ) -> io::Result<()> {
+    ) -> io::Result<Option<(String, CharRange)>> {
         let mut obj_params = ObjParamParser::new(&mut cursor)?;
         if let Some(param) = obj_params.next() {
             let param = param?;
@@ -40,7 +92,10 @@ impl Registers {
                 x if x.map(|x| x == "MARGIN").unwrap_or_default() => {
                     let margin_str: Cow<'_, str> = param.1.into();
                     match margin_str.parse() {
-                        Ok(c) => self.margin = c,
+                        Ok(c) => {
+                            self.margin = c;
+                            self.set.margin = true;
+                        }
                         Err(_) => errors.append_error(
                             ParseError::Syntax(obj_params.char_range(), "Not a valid float"),
                             obj_params.error_info(),
@@ -50,7 +105,10 @@ impl Registers {
                 x if x.map(|x| x == "MARGIN_PER").unwrap_or_default() => {
                     let margin_per_str: Cow<'_, str> = param.1.into();
                     match margin_per_str.parse() {
-                        Ok(c) => self.margin_per = c,
+                        Ok(c) => {
+                            self.margin_per = c;
+                            self.set.margin_per = true;
+                        }
                         Err(_) => errors.append_error(
                             ParseError::Syntax(obj_params.char_range(), "Not a valid float"),
                             obj_params.error_info(),
@@ -65,22 +123,16 @@ impl Registers {
                         obj_params.error_info(),
                         Arc::clone(&errors),
                     )?;
-                    let oklab_bg = oklab::srgb_f32_to_oklab(oklab::Rgb {
-                        r: bg.red,
-                        g: bg.green,
-                        b: bg.blue,
-                    });
-                    self.bg = BgColor {
-                        bg_l: oklab_bg.l,
-                        bg_a: oklab_bg.a,
-                        bg_b: oklab_bg.b,
-                        alpha: bg.alpha,
-                    };
+                    self.bg = bg_color_from_srgb(bg);
+                    self.set.bg = true;
                 }
                 x if x.map(|x| x == "CREATE_EDGES").unwrap_or_default() => {
                     let create_edges_str: Cow<'_, str> = param.1.into();
                     match create_edges_str.parse() {
-                        Ok(c) => self.create_edges = c,
+                        Ok(c) => {
+                            self.create_edges = c;
+                            self.set.create_edges = true;
+                        }
                         Err(_) => errors.append_error(
                             ParseError::Syntax(obj_params.char_range(), "Not a valid float"),
                             obj_params.error_info(),
@@ -90,13 +142,42 @@ impl Registers {
                 x if x.map(|x| x == "FLEX").unwrap_or_default() => {
                     let flex_str: Cow<'_, str> = param.1.into();
                     match flex_str.parse() {
-                        Ok(c) => self.flex = c,
+                        Ok(c) => {
+                            self.flex = c;
+                            self.set.flex = true;
+                        }
                         Err(_) => errors.append_error(
                             ParseError::Syntax(obj_params.char_range(), "Not a valid flex"),
                             obj_params.error_info(),
                         ),
                     }
                 }
+                x if x.map(|x| x == "INCLUDE").unwrap_or_default() => {
+                    let path: Cow<'_, str> = param.1.into();
+                    return Ok(Some((path.into_owned(), obj_params.char_range())));
+                }
+                x if x.map(|x| x == "THEME").unwrap_or_default() => {
+                    let path: Cow<'_, str> = param.1.into();
+                    match load_theme(Path::new(&resolve_include(path_to_grz, path.as_ref()))) {
+                        Ok(theme) => {
+                            self.bg = theme.bg;
+                            self.margin = theme.margin;
+                            self.margin_per = theme.margin_per;
+                            self.theme = theme;
+                            self.set.bg = true;
+                            self.set.margin = true;
+                            self.set.margin_per = true;
+                            self.set.theme = true;
+                        }
+                        Err(e) => errors.append_error(
+                            ParseError::Theme(
+                                obj_params.char_range(),
+                                format!("Failed to load `{}`: {}", path, e),
+                            ),
+                            obj_params.error_info(),
+                        ),
+                    }
+                }
                 x if x.map(|x| x == "INVERT").unwrap_or_default() => {
                     // unimplemented
                 }
@@ -116,6 +197,21 @@ impl Registers {
                 ),
             }
         }
-        Ok(())
+        Ok(None)
+    }
+}
+
+/// Background colors are interpolated in the Oklab color space
+pub fn bg_color_from_srgb(bg: Srgb) -> BgColor {
+    let oklab_bg = oklab::srgb_f32_to_oklab(oklab::Rgb {
+        r: bg.red,
+        g: bg.green,
+        b: bg.blue,
+    });
+    BgColor {
+        bg_l: oklab_bg.l,
+        bg_a: oklab_bg.a,
+        bg_b: oklab_bg.b,
+        alpha: bg.alpha,
     }
 }
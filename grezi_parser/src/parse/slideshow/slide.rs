@@ -8,11 +8,12 @@ use std::{
 use emath::{Align2, Rect};
 use smallvec::SmallVec;
 use tracing::instrument;
-use tree_sitter_grz::NodeKind;
+use tree_sitter_grz::{FieldName, NodeKind};
 
 use crate::{
     parse::{
-        cursor::{GrzCursor, GrzCursorGuard, GrzCursorGuardRaw},
+        CharRange,
+        cursor::{ErrorInfo, GrzCursor, GrzCursorGuard, GrzCursorGuardRaw},
         error::{ErrsWithSource, ParseError},
     },
     slide::{ObjPositions, ObjState, Slide, SlideObj, SlideVb, ViewboxRef},
@@ -84,9 +85,11 @@ impl Slide {
         }
 
         if let Some(actions_block_cursor) = cursor.goto_first_child(NodeKind::SymSlideFunctions)? {
-            let drawable_actions = self
-                .slide_params
-                .parse(actions_block_cursor, Arc::clone(&errors))?;
+            let drawable_actions = self.slide_params.parse(
+                actions_block_cursor,
+                self.theme.highlight,
+                Arc::clone(&errors),
+            )?;
 
             self.actions = drawable_actions;
         }
@@ -94,9 +97,72 @@ impl Slide {
         Ok(())
     }
 
+    /// Warns about `highlight` and `line` actions that reference
+    /// objects which aren't on this slide. This runs on reused
+    /// slides too, since they aren't parsed again
+    pub fn check_actions(&self, cursor: &GrzCursor, errors: &ErrsWithSource) -> io::Result<()> {
+        let ErrorInfo { source, tree } = cursor.error_info();
+        let mut node_cursor = cursor.node().walk();
+        let Some(functions) = cursor
+            .node()
+            .named_children(&mut node_cursor)
+            .find(|n| NodeKind::from(n.kind_id()) == NodeKind::SymSlideFunctions)
+        else {
+            return Ok(());
+        };
+
+        let mut functions_cursor = functions.walk();
+        for function in functions
+            .named_children(&mut functions_cursor)
+            .filter(|n| NodeKind::from(n.kind_id()) == NodeKind::SymSlideFunction)
+        {
+            let Some(name) = function.child_by_field_id(FieldName::FieldFunction as u16) else {
+                continue;
+            };
+            let object_arguments: &[usize] = match source.byte_slice(name.byte_range()) {
+                x if x == "highlight" => &[0],
+                x if x == "line" => &[0, 2],
+                _ => continue,
+            };
+
+            let mut function_cursor = function.walk();
+            for (index, argument) in function
+                .named_children(&mut function_cursor)
+                .filter(|n| !n.is_extra() && n.id() != name.id())
+                .enumerate()
+            {
+                if !object_arguments.contains(&index)
+                    || NodeKind::from(argument.kind_id()) != NodeKind::SymIdentifier
+                {
+                    continue;
+                }
+                let object: smartstring::alias::String =
+                    source.byte_slice(argument.byte_range()).chars().collect();
+                if !self.objects.contains_key(&object) {
+                    errors.append_error(
+                        ParseError::ObjectNotOnSlide(
+                            CharRange::new(argument.range(), source)?,
+                            object.to_string(),
+                        ),
+                        ErrorInfo { source, tree },
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn apply_registers(&mut self, registers: &Registers) {
         self.create_edges = registers.create_edges;
         self.bg = registers.bg;
+        self.theme = Arc::clone(&registers.theme);
+    }
+
+    /// Whether the slide was parsed with a different
+    /// theme, and its highlights need a new color
+    pub fn theme_changed(&self, registers: &Registers) -> bool {
+        self.theme.generation != registers.theme.generation
     }
 
     pub fn make_action(&mut self) {
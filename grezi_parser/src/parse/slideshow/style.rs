@@ -0,0 +1,206 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    io,
+    ops::DerefMut,
+    sync::Arc,
+};
+
+use ecolor::Color32;
+use ropey::Rope;
+use tracing::instrument;
+use tree_sitter::Node;
+use tree_sitter_grz::{FieldName, NodeKind};
+
+use crate::{
+    GrzRoot,
+    parse::{
+        cursor::GrzCursorGuard,
+        error::{ErrsWithSource, ParseError},
+    },
+    text::{Align, Attrs, Weight},
+};
+
+use super::object::{ObjParamParser, next_generation, parse_align, parse_color};
+
+pub type Styles = HashMap<smartstring::alias::String, NamedStyle, ahash::RandomState>;
+
+#[derive(Debug, Clone)]
+pub struct NamedStyle {
+    pub style: TextStyle,
+    /// Used to remove styles that are no longer in the syntax tree
+    pub present: bool,
+    /// Changes every time the style is parsed with
+    /// different properties
+    pub generation: u64,
+}
+
+/// Text properties declared once and shared by name, like
+/// `Accent: Style(font_family: "Inter:bold", color: "#f80")`.
+/// Text objects pick one with `style: "Accent"`, and djot
+/// with `[some text]{.Accent}`. Properties that aren't
+/// given are left alone
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TextStyle {
+    /// Anything `Attrs::apply_fontstr` understands
+    pub font_family: Option<String>,
+    /// The size of a text object or a block, or
+    /// of just the text of a span
+    pub font_size: Option<f32>,
+    pub color: Option<Color32>,
+    pub weight: Option<Weight>,
+    /// Only used by text objects, not by djot
+    pub line_height: Option<f32>,
+    /// Only used by text objects, not by djot
+    pub align: Option<Align>,
+}
+
+impl TextStyle {
+    /// Parse name before entering this function
+    #[instrument(skip_all, fields(source = %cursor.parent_source()?, type = std::any::type_name::<Self>()))]
+    pub fn parse(mut cursor: GrzCursorGuard, errors: Arc<ErrsWithSource>) -> io::Result<Self> {
+        let mut style = TextStyle::default();
+        let mut obj_params = ObjParamParser::new(cursor.deref_mut())?;
+        while let Some(param) = obj_params.next() {
+            let param = param?;
+
+            match param.0 {
+                x if x.map(|x| x == "font_family").unwrap_or_default() => {
+                    let family: Cow<'_, str> = param.1.into();
+                    style.font_family = Some(family.into_owned());
+                }
+                x if x.map(|x| x == "font_size").unwrap_or_default() => {
+                    let font_size_str: Cow<'_, str> = param.1.into();
+                    match font_size_str.parse() {
+                        Ok(c) => style.font_size = Some(c),
+                        Err(_) => errors.append_error(
+                            ParseError::Syntax(obj_params.char_range(), "Not a valid float"),
+                            obj_params.error_info(),
+                        ),
+                    }
+                }
+                x if x.map(|x| x == "color").unwrap_or_default() => {
+                    let color_str: Cow<'_, str> = param.1.into();
+                    style.color = Some(parse_color(
+                        color_str.as_ref(),
+                        obj_params.char_range(),
+                        obj_params.error_info(),
+                        Arc::clone(&errors),
+                    )?);
+                }
+                x if x.map(|x| x == "weight").unwrap_or_default() => {
+                    let weight_str: Cow<'_, str> = param.1.into();
+                    match Weight::from_name(weight_str.as_ref()) {
+                        Some(weight) => style.weight = Some(weight),
+                        None => errors.append_error(
+                            ParseError::NotFound(
+                                obj_params.char_range(),
+                                "That font weight does not exist",
+                            ),
+                            obj_params.error_info(),
+                        ),
+                    }
+                }
+                x if x.map(|x| x == "line_height").unwrap_or_default() => {
+                    let line_height_str: Cow<'_, str> = param.1.into();
+                    match line_height_str.parse() {
+                        Ok(c) => {
+                            if c <= 0.0 {
+                                errors.append_error(
+                                    ParseError::Syntax(
+                                        obj_params.char_range(),
+                                        "Line height must be greater than 0",
+                                    ),
+                                    obj_params.error_info(),
+                                );
+                            } else {
+                                style.line_height = Some(c)
+                            }
+                        }
+                        Err(_) => errors.append_error(
+                            ParseError::Syntax(obj_params.char_range(), "Not a valid float"),
+                            obj_params.error_info(),
+                        ),
+                    }
+                }
+                x if x.map(|x| x == "align").unwrap_or_default() => {
+                    style.align = Some(parse_align(
+                        param.1.as_rope_slice(),
+                        obj_params.char_range(),
+                        obj_params.error_info(),
+                        Arc::clone(&errors),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(style)
+    }
+
+    /// Applies the font family, color and weight of this style
+    pub fn apply(&self, attrs: &mut Attrs) {
+        if let Some(font_family) = &self.font_family {
+            attrs.apply_fontstr(font_family);
+        }
+        if let Some(color) = self.color {
+            attrs.color = color;
+        }
+        if let Some(weight) = self.weight {
+            attrs.weight = weight;
+        }
+    }
+}
+
+/// Whether an object declares a style
+/// instead, like `Accent: Style(color: "#f80")`
+pub fn declares_style(object: Node, source: &Rope) -> bool {
+    let mut cursor = object.walk();
+    object
+        .named_children(&mut cursor)
+        .find(|n| NodeKind::from(n.kind_id()) == NodeKind::SymObjInner)
+        .and_then(|inner| inner.child_by_field_id(FieldName::FieldTy as u16))
+        .is_some_and(|ty| source.byte_slice(ty.byte_range()) == "Style")
+}
+
+/// Identifies the styles that are declared at some point
+/// in the file, so that text objects parsed with them can
+/// tell when one of them changed or went away
+pub fn styles_fingerprint(styles: &Styles) -> u64 {
+    let mut present: Vec<_> = styles
+        .iter()
+        .filter(|(_, style)| style.present)
+        .map(|(name, style)| (name, style.generation))
+        .collect();
+    present.sort_unstable();
+    let mut hasher = DefaultHasher::new();
+    present.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl GrzRoot {
+    /// Keeps the generation of a style that was
+    /// parsed again without changing
+    pub fn declare_style(&mut self, name: smartstring::alias::String, style: TextStyle) {
+        match self.styles.get_mut(&name) {
+            Some(named) => {
+                if named.style != style {
+                    named.style = style;
+                    named.generation = next_generation();
+                }
+                named.present = true;
+            }
+            None => {
+                self.styles.insert(
+                    name,
+                    NamedStyle {
+                        style,
+                        present: true,
+                        generation: next_generation(),
+                    },
+                );
+            }
+        }
+    }
+}
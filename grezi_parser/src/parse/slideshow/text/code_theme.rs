@@ -0,0 +1,329 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, LazyLock},
+    time::SystemTime,
+};
+
+use css_color::Srgb;
+use ecolor::Color32;
+use parking_lot::Mutex;
+use smallvec::SmallVec;
+
+use crate::{
+    parse::{
+        helix_loader,
+        slideshow::{include::resolve_include, object::next_generation},
+    },
+    text::{Modifier, Style, Weight},
+};
+
+/// The highlights code is split into. Any other capture
+/// falls back to the closest of these
+pub const THEME_NAMES: &[&str] = &[
+    "attribute",
+    "comment",
+    "constant",
+    "constant.builtin",
+    "constant.character",
+    "constant.character.escape",
+    "constant.numeric",
+    "constructor",
+    "diff.delta",
+    "diff.minus",
+    "diff.plus",
+    "function",
+    "function.builtin",
+    "function.macro",
+    "keyword",
+    "keyword.function",
+    "keyword.control",
+    "keyword.directive",
+    "label",
+    "module",
+    "namespace",
+    "operator",
+    "punctuation",
+    "punctuation.delimiter",
+    "special",
+    "string",
+    "string.regexp",
+    "tag",
+    "type",
+    "type.builtin",
+    "type.enum.variant",
+    "variable",
+    "variable.builtin",
+    "variable.other.member",
+    "variable.parameter",
+    "markup.heading",
+    "markup.list",
+    "markup.bold",
+    "markup.italic",
+    "markup.strikethrough",
+    "markup.link.url",
+    "markup.link.text",
+    "markup.quote",
+    "markup.raw",
+    "ui.background",
+    "ui.popup",
+    "ui.help",
+    "ui.menu",
+    "ui.cursor",
+    "ui.cursor.primary",
+    "ui.cursor.match",
+    "ui.linenr",
+    "ui.linenr.selected",
+    "ui.statusline",
+    "ui.statusline.inactive",
+    "ui.statusline.insert",
+    "ui.statusline.select",
+    "ui.bufferline",
+    "ui.bufferline.active",
+    "ui.text",
+    "ui.text.focus",
+    "ui.text.directory",
+    "ui.text.inactive",
+    "ui.virtual.whitespace",
+    "ui.virtual.indent-guide",
+    "ui.virtual.inlay-hint",
+    "ui.debug.active",
+    "ui.debug.breakpoint",
+    "ui.picker.header.column.active",
+    "warning",
+    "error",
+    "info",
+    "hint",
+    "diagnostic.error.underline",
+    "diagnostic.underline",
+    "diagnostic.deprecated",
+];
+
+/// Themes that work without Helix being installed
+const BUNDLED_THEMES: &[(&str, &str)] = &[
+    ("dark_plus", include_str!("themes/dark_plus.toml")),
+    ("light_plus", include_str!("themes/light_plus.toml")),
+];
+
+/// How many themes deep `inherits` can go
+const MAX_INHERITS: usize = 8;
+
+static DARK_PLUS: LazyLock<CodeTheme> =
+    LazyLock::new(|| CodeTheme::from_helix(&toml::from_str(BUNDLED_THEMES[0].1).unwrap()));
+
+/// The code themes loaded by name and the file they're relative
+/// to, so that they're only read again when one of their files is
+static CODE_THEMES: LazyLock<Mutex<HashMap<(String, String), LoadedCodeTheme>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+struct LoadedCodeTheme {
+    /// Every file the theme was read from, including the
+    /// ones it inherits from, and when they were changed
+    files: Vec<(PathBuf, SystemTime)>,
+    theme: CodeTheme,
+    generation: u64,
+}
+
+/// How each of the highlights in `THEME_NAMES` is drawn.
+/// Defaults to Dark+
+#[derive(Debug, Clone)]
+pub struct CodeTheme(Arc<[SmallVec<[Modifier; 2]>]>);
+
+impl Default for CodeTheme {
+    fn default() -> Self {
+        DARK_PLUS.clone()
+    }
+}
+
+impl CodeTheme {
+    /// Builds a theme out of scopes like `keyword.control`. Like
+    /// in Helix, a scope that isn't given falls back to its parent
+    pub fn from_scopes(scopes: &HashMap<String, SmallVec<[Modifier; 2]>>) -> Self {
+        CodeTheme(
+            THEME_NAMES
+                .iter()
+                .map(|name| {
+                    let mut scope = *name;
+                    loop {
+                        if let Some(modifiers) = scopes.get(scope) {
+                            return modifiers.clone();
+                        }
+                        match scope.rfind('.') {
+                            Some(parent) => scope = &scope[..parent],
+                            None => return SmallVec::new(),
+                        }
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Builds a theme out of a Helix theme, where each scope is
+    /// either a color or a table like `{ fg = "red", modifiers =
+    /// ["bold"] }`, and colors can be named in a `[palette]`.
+    /// Colors that aren't understood, like terminal colors, and
+    /// modifiers that only make sense in a terminal are skipped
+    pub fn from_helix(theme: &toml::Table) -> Self {
+        let palette = theme.get("palette").and_then(|palette| palette.as_table());
+        let color = |name: &str| {
+            let color = palette
+                .and_then(|palette| palette.get(name))
+                .and_then(|color| color.as_str())
+                .unwrap_or(name);
+            Srgb::from_str(color).ok().map(|c| {
+                Color32::from_rgba_unmultiplied(
+                    (c.red * 255.0) as u8,
+                    (c.green * 255.0) as u8,
+                    (c.blue * 255.0) as u8,
+                    (c.alpha * 255.0) as u8,
+                )
+            })
+        };
+
+        let scopes = theme
+            .iter()
+            .filter(|(scope, _)| *scope != "palette" && *scope != "inherits")
+            .map(|(scope, style)| {
+                let mut modifiers = SmallVec::new();
+                match style {
+                    toml::Value::String(fg) => modifiers.extend(color(fg).map(Modifier::Color)),
+                    toml::Value::Table(style) => {
+                        modifiers.extend(
+                            style
+                                .get("fg")
+                                .and_then(|fg| fg.as_str())
+                                .and_then(color)
+                                .map(Modifier::Color),
+                        );
+                        for modifier in style
+                            .get("modifiers")
+                            .and_then(|modifiers| modifiers.as_array())
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|modifier| modifier.as_str())
+                        {
+                            match modifier {
+                                "bold" => modifiers.push(Modifier::Weight(Weight::BOLD)),
+                                "italic" => modifiers.push(Modifier::Style(Style::Italic)),
+                                "underlined" => modifiers.push(Modifier::Underline),
+                                "crossed_out" => modifiers.push(Modifier::Strikethrough),
+                                _ => {}
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                (scope.clone(), modifiers)
+            })
+            .collect();
+        Self::from_scopes(&scopes)
+    }
+
+    pub fn modifiers(&self, highlight: usize) -> &[Modifier] {
+        self.0
+            .get(highlight)
+            .map(|m| m.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The modifiers of one of the `THEME_NAMES`, like `ui.linenr`
+    pub fn scope(&self, name: &str) -> &[Modifier] {
+        THEME_NAMES
+            .iter()
+            .position(|n| *n == name)
+            .map(|highlight| self.modifiers(highlight))
+            .unwrap_or_default()
+    }
+}
+
+/// Loads a code theme by name, or from a path to a `.toml` file
+/// relative to `relative_to`, along with a generation that changes
+/// when any of its files do. The last theme loaded the same way is
+/// reused if none of its files have changed since
+pub fn load_code_theme(name: &str, relative_to: &str) -> Result<(CodeTheme, u64), String> {
+    let key = (name.to_string(), relative_to.to_string());
+    if let Some(loaded) = CODE_THEMES.lock().get(&key) {
+        let unchanged = loaded.files.iter().all(|(path, loaded)| {
+            std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified == *loaded)
+        });
+        if unchanged {
+            return Ok((loaded.theme.clone(), loaded.generation));
+        }
+    }
+
+    let mut files = Vec::new();
+    let theme = CodeTheme::from_helix(&code_theme_table_inner(name, relative_to, 0, &mut files)?);
+    let generation = next_generation();
+    CODE_THEMES.lock().insert(
+        key,
+        LoadedCodeTheme {
+            files,
+            theme: theme.clone(),
+            generation,
+        },
+    );
+    Ok((theme, generation))
+}
+
+/// Reads a Helix theme, with the themes it inherits from merged
+/// in. Themes are looked for in the Helix config directory, then
+/// the Helix runtime, then the themes bundled with Grezi
+pub fn code_theme_table(name: &str, relative_to: &str) -> Result<toml::Table, String> {
+    code_theme_table_inner(name, relative_to, 0, &mut Vec::new())
+}
+
+/// Also records each file that's read into `files`,
+/// along with when it was changed
+fn code_theme_table_inner(
+    name: &str,
+    relative_to: &str,
+    depth: usize,
+    files: &mut Vec<(PathBuf, SystemTime)>,
+) -> Result<toml::Table, String> {
+    if depth > MAX_INHERITS {
+        return Err(format!("`{}` inherits from too many themes", name));
+    }
+
+    let mut read = |path: PathBuf| -> Result<String, String> {
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| e.to_string())?;
+        let source = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        files.push((path, modified));
+        Ok(source)
+    };
+    let source: Cow<'_, str> = if name.ends_with(".toml") {
+        read(PathBuf::from(resolve_include(relative_to, name)))?.into()
+    } else if let Some(path) = helix_loader::find_theme_file(name) {
+        read(path)?.into()
+    } else if let Some((_, source)) = BUNDLED_THEMES.iter().find(|(bundled, _)| *bundled == name) {
+        Cow::Borrowed(source)
+    } else {
+        return Err(format!("There is no theme named `{}`", name));
+    };
+    let mut theme: toml::Table = toml::from_str(&source).map_err(|e| e.to_string())?;
+
+    let Some(parent) = theme.remove("inherits") else {
+        return Ok(theme);
+    };
+    let parent = parent
+        .as_str()
+        .ok_or_else(|| "`inherits` needs to be the name of a theme".to_string())?;
+    let mut base = code_theme_table_inner(parent, relative_to, depth + 1, files)?;
+    let palette = theme.remove("palette");
+    base.extend(theme);
+    match (base.get_mut("palette"), palette) {
+        (Some(toml::Value::Table(base_palette)), Some(toml::Value::Table(palette))) => {
+            base_palette.extend(palette)
+        }
+        (_, Some(palette)) => {
+            base.insert("palette".to_string(), palette);
+        }
+        _ => {}
+    }
+    Ok(base)
+}
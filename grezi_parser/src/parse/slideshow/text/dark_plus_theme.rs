@@ -1,173 +0,0 @@
-use crate::text::{Modifier, Style, Weight};
-use ecolor::Color32;
-
-pub const THEME_NAMES: &[&str] = &[
-    "attribute",
-    "comment",
-    "constant",
-    "constant.builtin",
-    "constant.character",
-    "constant.character.escape",
-    "constant.numeric",
-    "constructor",
-    "diff.delta",
-    "diff.minus",
-    "diff.plus",
-    "function",
-    "function.builtin",
-    "function.macro",
-    "keyword",
-    "keyword.function",
-    "keyword.control",
-    "keyword.directive",
-    "label",
-    "module",
-    "namespace",
-    "operator",
-    "punctuation",
-    "punctuation.delimiter",
-    "special",
-    "string",
-    "string.regexp",
-    "tag",
-    "type",
-    "type.builtin",
-    "type.enum.variant",
-    "variable",
-    "variable.builtin",
-    "variable.other.member",
-    "variable.parameter",
-    "markup.heading",
-    "markup.list",
-    "markup.bold",
-    "markup.italic",
-    "markup.strikethrough",
-    "markup.link.url",
-    "markup.link.text",
-    "markup.quote",
-    "markup.raw",
-    "ui.background",
-    "ui.popup",
-    "ui.help",
-    "ui.menu",
-    "ui.cursor",
-    "ui.cursor.primary",
-    "ui.cursor.match",
-    "ui.linenr",
-    "ui.linenr.selected",
-    "ui.statusline",
-    "ui.statusline.inactive",
-    "ui.statusline.insert",
-    "ui.statusline.select",
-    "ui.bufferline",
-    "ui.bufferline.active",
-    "ui.text",
-    "ui.text.focus",
-    "ui.text.directory",
-    "ui.text.inactive",
-    "ui.virtual.whitespace",
-    "ui.virtual.indent-guide",
-    "ui.virtual.inlay-hint",
-    "ui.debug.active",
-    "ui.debug.breakpoint",
-    "ui.picker.header.column.active",
-    "warning",
-    "error",
-    "info",
-    "hint",
-    "diagnostic.error.underline",
-    "diagnostic.underline",
-    "diagnostic.deprecated",
-];
-
-macro_rules! hex_color {
-    ($color:expr) => {{
-        let color = color_hex::color_from_hex!($color);
-        Color32::from_rgb(color[0], color[1], color[2])
-    }};
-}
-
-pub const THEME_COLORS: &[&[Modifier]] = &[
-    &[Modifier::Color(hex_color!("#9CDCFE"))],
-    &[Modifier::Color(hex_color!("#6A9955"))],
-    &[Modifier::Color(hex_color!("#4FC1FF"))],
-    &[Modifier::Color(hex_color!("#569CD6"))],
-    &[Modifier::Color(hex_color!("#ce9178"))],
-    &[Modifier::Color(hex_color!("#d7ba7d"))],
-    &[Modifier::Color(hex_color!("#b5cea8"))],
-    &[Modifier::Color(hex_color!("#4EC9B0"))],
-    &[Modifier::Color(hex_color!("#1b81a8"))],
-    &[Modifier::Color(hex_color!("#f14c4c"))],
-    &[Modifier::Color(hex_color!("#487e02"))],
-    &[Modifier::Color(hex_color!("#DCDCAA"))],
-    &[Modifier::Color(hex_color!("#DCDCAA"))],
-    &[Modifier::Color(hex_color!("#569CD6"))],
-    &[Modifier::Color(hex_color!("#569CD6"))],
-    &[Modifier::Color(hex_color!("#569CD6"))],
-    &[Modifier::Color(hex_color!("#C586C0"))],
-    &[Modifier::Color(hex_color!("#C586C0"))],
-    &[Modifier::Color(hex_color!("#569CD6"))],
-    &[Modifier::Color(hex_color!("#4EC9B0"))],
-    &[Modifier::Color(hex_color!("#4EC9B0"))],
-    &[Modifier::Color(hex_color!("#d4d4d4"))],
-    &[Modifier::Color(hex_color!("#d4d4d4"))],
-    &[Modifier::Color(hex_color!("#d4d4d4"))],
-    &[Modifier::Color(hex_color!("#d4d4d4"))],
-    &[Modifier::Color(hex_color!("#ce9178"))],
-    &[Modifier::Color(hex_color!("#d7ba7d"))],
-    &[Modifier::Color(hex_color!("#569CD6"))],
-    &[Modifier::Color(hex_color!("#4EC9B0"))],
-    &[Modifier::Color(hex_color!("#4EC9B0"))],
-    &[Modifier::Color(hex_color!("#4FC1FF"))],
-    &[Modifier::Color(hex_color!("#9CDCFE"))],
-    &[Modifier::Color(hex_color!("#569CD6"))],
-    &[Modifier::Color(hex_color!("#9CDCFE"))],
-    &[Modifier::Color(hex_color!("#9CDCFE"))],
-    &[
-        Modifier::Color(hex_color!("#569CD6")),
-        Modifier::Weight(Weight::BOLD),
-    ],
-    &[Modifier::Color(hex_color!("#6796E6"))],
-    &[Modifier::Weight(Weight::BOLD)],
-    &[Modifier::Style(Style::Italic)],
-    &[Modifier::Strikethrough],
-    &[
-        Modifier::Color(hex_color!("#007acc")),
-        Modifier::Style(Style::Italic),
-    ],
-    &[Modifier::Color(hex_color!("#ce9178"))],
-    &[Modifier::Color(hex_color!("#6A9955"))],
-    &[Modifier::Color(hex_color!("#ce9178"))],
-    &[Modifier::Color(hex_color!("#1e1e1e"))],
-    &[Modifier::Color(hex_color!("#252526"))],
-    &[Modifier::Color(hex_color!("#252526"))],
-    &[Modifier::Color(hex_color!("#252526"))],
-    &[Modifier::Color(hex_color!("#a6a6a6"))],
-    &[Modifier::Color(hex_color!("#a6a6a6"))],
-    &[Modifier::Color(hex_color!("#3a3d41"))],
-    &[Modifier::Color(hex_color!("#858585"))],
-    &[Modifier::Color(hex_color!("#c6c6c6"))],
-    &[Modifier::Color(hex_color!("#007acc"))],
-    &[Modifier::Color(hex_color!("#252526"))],
-    &[Modifier::Color(hex_color!("#ffffff"))],
-    &[Modifier::Color(hex_color!("#ffffff"))],
-    &[Modifier::Color(hex_color!("#252526"))],
-    &[Modifier::Color(hex_color!("#007acc"))],
-    &[Modifier::Color(hex_color!("#d4d4d4"))],
-    &[Modifier::Color(hex_color!("#ffffff"))],
-    &[Modifier::Color(hex_color!("#6796E6"))],
-    &[Modifier::Color(hex_color!("#858585"))],
-    &[Modifier::Color(hex_color!("#3e3e3d"))],
-    &[Modifier::Color(hex_color!("#404040"))],
-    &[Modifier::Color(hex_color!("#8b949e"))],
-    &[Modifier::Color(hex_color!("#ffcc00"))],
-    &[Modifier::Color(hex_color!("#e51400"))],
-    &[Modifier::Color(hex_color!("#ffffff"))],
-    &[Modifier::Color(hex_color!("#cca700"))],
-    &[Modifier::Color(hex_color!("#ff1212"))],
-    &[Modifier::Color(hex_color!("#75beff"))],
-    &[Modifier::Color(hex_color!("#eeeeee"))],
-    &[Modifier::Color(hex_color!("#ff1212"))],
-    &[Modifier::Color(hex_color!("#d7ba7d"))],
-    &[Modifier::Strikethrough],
-];
@@ -0,0 +1,201 @@
+use std::{borrow::Cow, fmt::Write, ops::RangeInclusive};
+
+use smallvec::SmallVec;
+use smart_default::SmartDefault;
+
+use crate::text::{Attrs, TextParagraph, TextSection, TextTag};
+
+use super::{TextJobParams, syntax_highlighting::format_highlighted};
+
+/// How much the color of lines outside of
+/// `highlight_lines` is faded
+const DIM_AMOUNT: f32 = 0.35;
+
+/// The parameters only `Code` objects have
+#[derive(SmartDefault, Debug, Clone)]
+pub struct CodeParams {
+    pub line_numbers: bool,
+    /// The number of the first line
+    #[default = 1]
+    pub start_line: usize,
+    /// Lines that stay bright while the rest are faded,
+    /// numbered the same way as the line numbers
+    pub highlight_lines: Vec<RangeInclusive<usize>>,
+    /// Whether lines starting with `+` or `-` are
+    /// added or removed lines of a diff
+    pub diff: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DiffSign {
+    Added,
+    Removed,
+    Unchanged,
+}
+
+/// Parses line ranges like `2-4, 7`
+pub fn parse_line_ranges(ranges: &str) -> Option<Vec<RangeInclusive<usize>>> {
+    ranges
+        .split(',')
+        .map(|range| {
+            let range = range.trim();
+            match range.split_once('-') {
+                Some((start, end)) => {
+                    let start: usize = start.trim().parse().ok()?;
+                    let end: usize = end.trim().parse().ok()?;
+                    (start <= end).then_some(start..=end)
+                }
+                None => range.parse().ok().map(|line| line..=line),
+            }
+        })
+        .collect()
+}
+
+impl TextJobParams<'_> {
+    /// Lays code out one paragraph per line, so that the
+    /// lines of a `highlight` action are the lines of the code
+    pub(super) fn format_code(self, code: CodeParams) -> SmallVec<[TextSection; 1]> {
+        let value: Cow<'_, str> = self.value.clone().into();
+        let language: Cow<'_, str> = self.language.clone().into();
+        let value = value.trim_matches('\n');
+
+        let mut signs = Vec::new();
+        let source: Cow<'_, str> = if code.diff {
+            let mut stripped = String::with_capacity(value.len());
+            for line in value.split('\n') {
+                let (sign, line) = match line.as_bytes().first() {
+                    Some(b'+') => (DiffSign::Added, &line[1..]),
+                    Some(b'-') => (DiffSign::Removed, &line[1..]),
+                    Some(b' ') => (DiffSign::Unchanged, &line[1..]),
+                    _ => (DiffSign::Unchanged, line),
+                };
+                signs.push(sign);
+                stripped.push_str(line);
+                stripped.push('\n');
+            }
+            stripped.pop();
+            Cow::Owned(stripped)
+        } else {
+            Cow::Borrowed(value)
+        };
+
+        let mut highlighted = TextParagraph::default();
+        if language.is_empty()
+            || !format_highlighted(
+                source.as_ref(),
+                language.as_ref(),
+                &self.default_attrs,
+                &self.code_theme,
+                &mut highlighted,
+            )
+        {
+            highlighted.rich_text.clear();
+            highlighted
+                .rich_text
+                .push((source.as_ref().into(), self.default_attrs.clone()));
+        }
+
+        let mut lines = vec![self.new_code_line()];
+        for (text, attrs) in highlighted.rich_text {
+            let mut parts = text.split('\n');
+            if let Some(part) = parts.next() {
+                push_span(lines.last_mut().unwrap(), part, &attrs);
+            }
+            for part in parts {
+                let mut line = self.new_code_line();
+                push_span(&mut line, part, &attrs);
+                lines.push(line);
+            }
+        }
+
+        let number_width = code
+            .start_line
+            .saturating_add(lines.len().saturating_sub(1))
+            .to_string()
+            .len();
+        let lines = lines
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut line)| {
+                let number = code.start_line.saturating_add(index);
+                let sign = signs.get(index).copied().unwrap_or(DiffSign::Unchanged);
+                let highlighted = code.highlight_lines.is_empty()
+                    || code.highlight_lines.iter().any(|r| r.contains(&number));
+                // Added and removed lines are styled
+                // like their signs in the gutter
+                let diff_scope = match sign {
+                    DiffSign::Added => Some("diff.plus"),
+                    DiffSign::Removed => Some("diff.minus"),
+                    DiffSign::Unchanged => None,
+                };
+                for modifier in diff_scope
+                    .into_iter()
+                    .flat_map(|scope| self.code_theme.scope(scope))
+                {
+                    for (_, attrs) in &mut line.rich_text {
+                        attrs.apply_modifier(*modifier);
+                    }
+                }
+                if !highlighted || sign == DiffSign::Removed {
+                    for (_, attrs) in &mut line.rich_text {
+                        attrs.color = attrs.color.gamma_multiply(DIM_AMOUNT);
+                    }
+                }
+
+                let gutter = (code.line_numbers || code.diff).then(|| {
+                    let mut label = smartstring::alias::String::new();
+                    if code.line_numbers {
+                        write!(label, "{:>1$}", number, number_width).unwrap();
+                    }
+                    if code.diff {
+                        if code.line_numbers {
+                            label.push(' ');
+                        }
+                        label.push(match sign {
+                            DiffSign::Added => '+',
+                            DiffSign::Removed => '-',
+                            DiffSign::Unchanged => ' ',
+                        });
+                    }
+                    let scope = match sign {
+                        DiffSign::Added => "diff.plus",
+                        DiffSign::Removed => "diff.minus",
+                        DiffSign::Unchanged if highlighted && !code.highlight_lines.is_empty() => {
+                            "ui.linenr.selected"
+                        }
+                        DiffSign::Unchanged => "ui.linenr",
+                    };
+                    let mut attrs = self.default_attrs.clone();
+                    for modifier in self.code_theme.scope(scope) {
+                        attrs.apply_modifier(*modifier);
+                    }
+                    TextParagraph {
+                        rich_text: smallvec::smallvec![(label, attrs)],
+                        font_size: self.default_font_size,
+                        tag: self.tagged.then_some(TextTag::Label),
+                        ..Default::default()
+                    }
+                });
+
+                (gutter, line)
+            })
+            .collect();
+
+        smallvec::smallvec![TextSection::Code(lines)]
+    }
+
+    fn new_code_line(&self) -> TextParagraph {
+        TextParagraph {
+            font_size: self.default_font_size,
+            tag: self.tagged.then_some(TextTag::Code),
+            ..Default::default()
+        }
+    }
+}
+
+fn push_span(line: &mut TextParagraph, text: &str, attrs: &Attrs) {
+    let text = text.trim_end_matches('\r');
+    if !text.is_empty() {
+        line.rich_text.push((text.into(), attrs.clone()));
+    }
+}
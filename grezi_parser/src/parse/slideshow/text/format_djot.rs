@@ -1,52 +1,130 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, ops::Range, sync::Arc};
 
-use ecolor::Color32;
-use jotdown::{Container, ListKind, OrderedListNumbering, OrderedListStyle, Parser};
+use grezi_mathml::latex::latex_to_mathml;
+use jotdown::{
+    Alignment, Attributes, Container, LinkType, ListKind, OffsetIter, OrderedListNumbering,
+    OrderedListStyle, Parser,
+};
 use nominals::{
     Decimal, DigitCollection, LetterLower, LetterUpper, Nominal, RomanLower, RomanUpper,
 };
 use smallvec::SmallVec;
 
 use crate::{
-    HIGHLIGHT_COLOR_DEFAULT,
-    parse::slideshow::text::syntax_highlighting::format_highlighted,
-    text::{Family, Style, TextParagraph, TextSection, TextTag, Weight},
+    parse::slideshow::{
+        style::{Styles, TextStyle},
+        text::syntax_highlighting::{format_highlighted, language_available},
+    },
+    text::{
+        Align, Attrs, Baseline, Family, MATH_PLACEHOLDER, Math, Style, TableRow, TextParagraph,
+        TextSection, TextTable, TextTag, Weight,
+    },
 };
 
-use super::TextJobParams;
+use super::{TextError, TextErrorKind, TextJobParams};
+
+/// How much of the color of the text the
+/// lines between the rows of a table get
+const RULE_OPACITY: f32 = 0.5;
+
+/// The events of a djot parser, remembering
+/// where the last one was in the text
+pub struct DjotEvents<'s> {
+    events: OffsetIter<'s>,
+    range: Range<usize>,
+}
+
+impl<'s> Iterator for DjotEvents<'s> {
+    type Item = jotdown::Event<'s>;
+
+    fn next(&mut self) -> Option<jotdown::Event<'s>> {
+        let (event, range) = self.events.next()?;
+        self.range = range;
+        Some(event)
+    }
+}
 
 impl TextJobParams<'_> {
-    pub fn format_djot(mut self) -> SmallVec<[TextSection; 1]> {
+    pub fn format_djot(mut self) -> (SmallVec<[TextSection; 1]>, Vec<TextError>) {
         // TODO: implement parsing in `jotdown` that
         // doesn't require re-allocating the String
         let value: Cow<'_, str> = std::mem::take(&mut self.value).into();
-        let mut parser = Parser::new(value.as_ref());
+        let mut parser = DjotEvents {
+            events: Parser::new(value.as_ref()).into_offset_iter(),
+            range: 0..0,
+        };
 
         let mut job = SmallVec::new();
         while let Some(event) = parser.next() {
-            if let jotdown::Event::Start(container, _attributes) = event {
+            if let jotdown::Event::Start(container, attributes) = event {
                 if container.is_block_container() {
-                    self.format_block_container(container, &mut parser, &mut job);
+                    self.format_block_container(container, &attributes, &mut parser, &mut job);
                 } else if container.is_block() {
                     let mut paragraph = self.new_paragraph();
-                    paragraph.format_block(container, &mut parser, &self);
+                    paragraph.format_block(container, &attributes, &mut parser, &self);
                     job.push(TextSection::Paragraph(paragraph));
                 }
             }
         }
-        job
+
+        (job, self.errors.take())
+    }
+
+    /// Djot math is MathML when it starts with a tag, like
+    /// `$`<mfrac><mi>a</mi><mi>b</mi></mfrac>`$`, and is
+    /// otherwise LaTeX, which is shown as it's written
+    /// if it can't be converted. `start` is where the
+    /// source is in the value, for its errors
+    fn mathml_from_source(&self, source: &str, start: Option<usize>, display: bool) -> Arc<str> {
+        let trimmed = source.trim();
+        if trimmed.starts_with("<math") {
+            return trimmed.into();
+        } else if trimmed.starts_with('<') {
+            return format!("<math>{}</math>", trimmed).into();
+        }
+        match latex_to_mathml(source, display) {
+            Ok(mathml) => mathml.into(),
+            Err(error) => {
+                let mut mathml = String::from("<math><mtext>");
+                for c in trimmed.chars() {
+                    match c {
+                        '<' => mathml.push_str("&lt;"),
+                        '>' => mathml.push_str("&gt;"),
+                        '&' => mathml.push_str("&amp;"),
+                        c => mathml.push(c),
+                    }
+                }
+                mathml.push_str("</mtext></math>");
+                self.errors.borrow_mut().push(TextError {
+                    span: start.map(|start| start + error.span.start..start + error.span.end),
+                    kind: TextErrorKind::Math(error.message),
+                });
+                mathml.into()
+            }
+        }
+    }
+
+    /// Warns about code in `language` when there's no
+    /// grammar to highlight it with
+    fn check_language(&self, language: &str, span: Range<usize>) {
+        if !language_available(language) {
+            self.errors.borrow_mut().push(TextError {
+                span: Some(span),
+                kind: TextErrorKind::UnknownLanguage(language.to_owned()),
+            });
+        }
     }
 
     pub fn format_block_container(
         &mut self,
         container: Container<'_>,
-        parser: &mut Parser,
+        attributes: &Attributes<'_>,
+        parser: &mut DjotEvents,
         text_job: &mut SmallVec<[TextSection; 1]>,
     ) {
         match container {
             // Unsupported
             Container::Footnote { .. }
-            | Container::Table
             | Container::TableRow { .. }
             | Container::Section { .. }
             | Container::Div { .. }
@@ -78,6 +156,7 @@ impl TextJobParams<'_> {
                                     } else {
                                         None
                                     },
+                                    ..Default::default()
                                 },
                                 list_item.into_vec(),
                             ));
@@ -88,25 +167,80 @@ impl TextJobParams<'_> {
                 }
                 text_job.push(TextSection::List(list));
             }
+            Container::Table => {
+                let mut table = TextTable::default();
+                // Rules are drawn unless the table has `{rules=false}`
+                if attributes
+                    .get_value("rules")
+                    .is_none_or(|rules| rules.parts().collect::<String>() != "false")
+                {
+                    table.rules = Some(self.default_attrs.color.gamma_multiply(RULE_OPACITY));
+                }
+                let mut caption = None;
+                while let Some(event) = parser.next() {
+                    match event {
+                        jotdown::Event::Start(Container::TableRow { head }, _) => {
+                            table.rows.push(TableRow {
+                                head,
+                                cells: Vec::new(),
+                            });
+                        }
+                        jotdown::Event::Start(
+                            c @ Container::TableCell { alignment, head },
+                            attributes,
+                        ) => {
+                            let mut cell = self.new_paragraph();
+                            cell.format_block(c, &attributes, parser, self);
+                            if head {
+                                for (_, attrs) in &mut cell.rich_text {
+                                    attrs.weight = Weight::BOLD;
+                                }
+                            }
+                            let Some(row) = table.rows.last_mut() else {
+                                continue;
+                            };
+                            if table.alignments.len() <= row.cells.len() {
+                                table.alignments.push(match alignment {
+                                    Alignment::Unspecified | Alignment::Left => Align::Left,
+                                    Alignment::Center => Align::Center,
+                                    Alignment::Right => Align::Right,
+                                });
+                            }
+                            row.cells.push(cell);
+                        }
+                        jotdown::Event::Start(c @ Container::Caption, attributes) => {
+                            let mut paragraph = self.new_paragraph();
+                            paragraph.format_block(c, &attributes, parser, self);
+                            caption = Some(paragraph);
+                        }
+                        jotdown::Event::End(Container::Table) => break,
+                        _ => {}
+                    }
+                }
+                text_job.push(TextSection::Table(table));
+                if let Some(caption) = caption {
+                    text_job.push(TextSection::Paragraph(caption));
+                }
+            }
             _ => {}
         }
     }
 }
 
 fn format_textjob_djot(
-    parser: &mut Parser,
+    parser: &mut DjotEvents,
     container: Option<Container<'_>>,
     params: &mut TextJobParams,
 ) -> SmallVec<[TextSection; 1]> {
     let mut job = SmallVec::new();
     while let Some(event) = parser.next() {
         match event {
-            jotdown::Event::Start(container, _attributes) => {
+            jotdown::Event::Start(container, attributes) => {
                 if container.is_block_container() {
-                    params.format_block_container(container, parser, &mut job);
+                    params.format_block_container(container, &attributes, parser, &mut job);
                 } else if container.is_block() {
                     let mut paragraph = params.new_paragraph();
-                    paragraph.format_block(container, parser, params);
+                    paragraph.format_block(container, &attributes, parser, params);
                     job.push(TextSection::Paragraph(paragraph));
                 }
             }
@@ -129,10 +263,26 @@ impl TextParagraph {
     pub fn format_block(
         &mut self,
         container: Container<'_>,
-        parser: &mut Parser<'_>,
+        attributes: &Attributes<'_>,
+        parser: &mut DjotEvents<'_>,
         params: &TextJobParams,
     ) {
-        let mut attrs = params.default_attrs.clone();
+        // What the text of this block goes back to
+        // when inline formatting ends
+        let mut base_attrs = params.default_attrs.clone();
+        let mut block_font_size = None;
+        for style in class_styles(params.styles, attributes) {
+            style.apply(&mut base_attrs);
+            block_font_size = style.font_size.or(block_font_size);
+        }
+        if let Some(font_size) = block_font_size {
+            self.font_size = font_size;
+        }
+        let mut attrs = base_attrs.clone();
+        // The formatting to go back to when each span ends
+        let mut span_attrs: SmallVec<[Attrs; 2]> = SmallVec::new();
+        // Display formulas get a line to themselves
+        let mut after_display_math = false;
         match container {
             // Unsupported
             Container::TableCell { .. }
@@ -145,15 +295,11 @@ impl TextParagraph {
                 if params.tagged {
                     self.tag = Some(TextTag::Heading(level));
                 }
-                let l = match level {
-                    1 => 2.0,
-                    2 => 1.5,
-                    3 => 1.17,
-                    4 => 1.0,
-                    5 => 0.83,
-                    _ => 0.67,
-                };
-                self.font_size *= l;
+                let l = params.theme.heading_sizes[level.clamp(1, 6) as usize - 1];
+                // A style sets the size of the heading itself
+                if block_font_size.is_none() {
+                    self.font_size *= l;
+                }
             }
             Container::CodeBlock { language } => {
                 attrs.family = Family::Monospace;
@@ -161,9 +307,16 @@ impl TextParagraph {
                     self.tag = Some(TextTag::Code);
                 }
                 if !language.is_empty() {
+                    params.check_language(language, parser.range.clone());
                     match parser.next() {
                         Some(jotdown::Event::Str(code)) => {
-                            if format_highlighted(code.trim(), language, &attrs, self) {
+                            if format_highlighted(
+                                code.trim(),
+                                language,
+                                &attrs,
+                                &params.code_theme,
+                                self,
+                            ) {
                                 return;
                             }
                         }
@@ -215,12 +368,21 @@ impl TextParagraph {
                     get_last_rich_text!().0.push('–');
                 }
                 jotdown::Event::Softbreak | jotdown::Event::NonBreakingSpace => {
-                    get_last_rich_text!().0.push(' ');
+                    if std::mem::take(&mut after_display_math) {
+                        get_last_rich_text!().0.push('\n');
+                    } else {
+                        get_last_rich_text!().0.push(' ');
+                    }
                 }
                 jotdown::Event::Hardbreak | jotdown::Event::ThematicBreak(_) => {
                     get_last_rich_text!().0.push('\n');
                 }
-                jotdown::Event::Str(s) => get_last_rich_text!().0.push_str(s.as_ref()),
+                jotdown::Event::Str(s) => {
+                    if std::mem::take(&mut after_display_math) {
+                        get_last_rich_text!().0.push('\n');
+                    }
+                    get_last_rich_text!().0.push_str(s.as_ref())
+                }
                 jotdown::Event::Symbol(symbol) => {
                     if let Some(emoji) = emojis::get_by_shortcode(symbol.as_ref()) {
                         get_last_rich_text!().0.push_str(emoji.as_str());
@@ -228,27 +390,50 @@ impl TextParagraph {
                 }
                 jotdown::Event::Start(container, attributes) => match container {
                     // Unsupported
-                    Container::Span
-                    | Container::Link(..)
-                    | Container::Math { .. }
-                    | Container::Image(..)
-                    | Container::RawInline { .. }
-                    | Container::Subscript
-                    | Container::Superscript => {}
+                    Container::Image(..) | Container::RawInline { .. } => {}
+                    Container::Math { display } => {
+                        let mut source = String::new();
+                        let mut start = None;
+                        loop {
+                            match parser.next() {
+                                Some(jotdown::Event::Str(s)) => {
+                                    start.get_or_insert(parser.range.start);
+                                    source.push_str(s.as_ref());
+                                }
+                                Some(jotdown::Event::End(Container::Math { .. })) | None => break,
+                                _ => {}
+                            }
+                        }
+                        if display && self.rich_text.iter().any(|(text, _)| !text.is_empty()) {
+                            let last = get_last_rich_text!();
+                            if !last.0.ends_with('\n') {
+                                last.0.push('\n');
+                            }
+                        }
+                        self.rich_text
+                            .push((MATH_PLACEHOLDER.into(), attrs.clone()));
+                        self.math.push(Math {
+                            mathml: params.mathml_from_source(&source, start, display),
+                            display,
+                        });
+                        after_display_math = display;
+                        new_rich_text!();
+                    }
                     Container::Verbatim => {
                         attrs.family = Family::Monospace;
                         if let Some(lang) = attributes.get_value("lang") {
+                            let lang = lang.parts().collect::<smartstring::alias::String>();
+                            params.check_language(lang.as_str(), parser.range.clone());
                             match parser.next() {
                                 Some(jotdown::Event::Str(code)) => {
                                     if format_highlighted(
                                         code.as_ref(),
-                                        lang.parts()
-                                            .collect::<smartstring::alias::String>()
-                                            .as_str(),
+                                        lang.as_str(),
                                         &attrs,
+                                        &params.code_theme,
                                         self,
                                     ) {
-                                        attrs.family = params.default_attrs.family.clone();
+                                        attrs.family = base_attrs.family.clone();
                                         continue;
                                     }
                                 }
@@ -257,16 +442,44 @@ impl TextParagraph {
                         }
                         new_rich_text!();
                     }
+                    Container::Link(destination, kind) => {
+                        span_attrs.push(attrs.clone());
+                        attrs.link = Some(match kind {
+                            LinkType::Email => format!("mailto:{}", destination).into(),
+                            _ => destination.as_ref().into(),
+                        });
+                        attrs.color = params.theme.link;
+                        attrs.underline = true;
+                        new_rich_text!();
+                    }
+                    Container::Span => {
+                        span_attrs.push(attrs.clone());
+                        for style in class_styles(params.styles, &attributes) {
+                            style.apply(&mut attrs);
+                            // A span can't resize its paragraph,
+                            // so it only sizes its own text
+                            attrs.font_size = style.font_size.or(attrs.font_size);
+                        }
+                        new_rich_text!();
+                    }
                     Container::Insert => {
-                        attrs.color = Color32::GREEN;
+                        attrs.underline = true;
                         new_rich_text!();
                     }
                     Container::Delete => {
-                        attrs.color = Color32::RED;
+                        attrs.strikethrough = true;
+                        new_rich_text!();
+                    }
+                    Container::Subscript => {
+                        attrs.baseline = Baseline::Subscript;
+                        new_rich_text!();
+                    }
+                    Container::Superscript => {
+                        attrs.baseline = Baseline::Superscript;
                         new_rich_text!();
                     }
                     Container::Mark => {
-                        attrs.color = HIGHLIGHT_COLOR_DEFAULT;
+                        attrs.color = params.theme.highlight;
                         new_rich_text!();
                     }
                     Container::Strong => {
@@ -289,27 +502,39 @@ impl TextParagraph {
 
                     match c {
                         // Unsupported
-                        Container::Span
-                        | Container::Link(..)
-                        | Container::Math { .. }
-                        | Container::Image(..)
-                        | Container::RawInline { .. }
-                        | Container::Subscript
-                        | Container::Superscript => {}
+                        Container::Image(..) | Container::RawInline { .. } => {}
+                        Container::Link(..) | Container::Span => {
+                            if let Some(previous) = span_attrs.pop() {
+                                attrs = previous;
+                            }
+                            new_rich_text!();
+                        }
                         Container::Verbatim => {
-                            attrs.family = params.default_attrs.family.clone();
+                            attrs.family = base_attrs.family.clone();
                             new_rich_text!();
                         }
-                        Container::Insert | Container::Delete | Container::Mark => {
-                            attrs.color = params.default_attrs.color;
+                        Container::Insert => {
+                            attrs.underline = base_attrs.underline;
+                            new_rich_text!();
+                        }
+                        Container::Delete => {
+                            attrs.strikethrough = base_attrs.strikethrough;
+                            new_rich_text!();
+                        }
+                        Container::Subscript | Container::Superscript => {
+                            attrs.baseline = base_attrs.baseline;
+                            new_rich_text!();
+                        }
+                        Container::Mark => {
+                            attrs.color = base_attrs.color;
                             new_rich_text!();
                         }
                         Container::Strong => {
-                            attrs.weight = params.default_attrs.weight;
+                            attrs.weight = base_attrs.weight;
                             new_rich_text!();
                         }
                         Container::Emphasis => {
-                            attrs.style = params.default_attrs.style;
+                            attrs.style = base_attrs.style;
                             new_rich_text!();
                         }
                         _ => {}
@@ -321,6 +546,24 @@ impl TextParagraph {
     }
 }
 
+/// The styles named by the classes of a djot element,
+/// like `[text]{.Accent}`. Unknown classes are ignored
+fn class_styles<'a>(
+    styles: Option<&'a Styles>,
+    attributes: &Attributes<'_>,
+) -> SmallVec<[&'a TextStyle; 1]> {
+    let (Some(styles), Some(classes)) = (styles, attributes.get_value("class")) else {
+        return SmallVec::new();
+    };
+    let classes: smartstring::alias::String = classes.parts().collect();
+    classes
+        .split_whitespace()
+        .filter_map(|class| styles.get(class))
+        .filter(|style| style.present)
+        .map(|style| &style.style)
+        .collect()
+}
+
 struct ListNumberer {
     list_kind: ListKind,
     at: u64,
@@ -1,9 +1,10 @@
-use std::{borrow::Cow, io, sync::Arc};
+use std::{borrow::Cow, cell::RefCell, io, ops::Range, sync::Arc};
 
 use ropey::RopeSlice;
 use smallvec::SmallVec;
 use smart_default::SmartDefault;
 use syntax_highlighting::format_highlighted;
+pub use syntax_highlighting::language_available;
 use tracing::instrument;
 use tree_sitter_grz::NodeKind;
 
@@ -15,7 +16,12 @@ use crate::{
     text::{Attrs, TextParagraph, TextSection, TextTag},
 };
 
-mod dark_plus_theme;
+use super::{style::Styles, theme::Theme};
+use code_theme::CodeTheme;
+pub use format_code::{CodeParams, parse_line_ranges};
+
+pub mod code_theme;
+mod format_code;
 mod format_djot;
 mod syntax_highlighting;
 
@@ -27,37 +33,72 @@ pub struct TextJobParams<'a> {
     pub value: StringLiteral<'a>,
     #[default = true]
     pub tagged: bool,
+    /// Styles djot can use through classes, like `[text]{.Accent}`
+    pub styles: Option<&'a Styles>,
+    /// Heading sizes and the highlight color
+    pub theme: Arc<Theme>,
+    pub code_theme: CodeTheme,
+    /// Set for `Code` objects
+    pub code: Option<CodeParams>,
+    /// Mistakes found in `value` while formatting it
+    pub errors: RefCell<Vec<TextError>>,
+}
+
+/// A mistake in the text of an object
+pub struct TextError {
+    /// Where the mistake is in `value`, if it could be found
+    pub span: Option<Range<usize>>,
+    pub kind: TextErrorKind,
+}
+
+pub enum TextErrorKind {
+    /// LaTeX that couldn't be converted, with the message
+    Math(String),
+    /// A code block in a language with no grammar
+    UnknownLanguage(String),
 }
 
 impl From<TextJobParams<'_>> for SmallVec<[TextSection; 1]> {
     fn from(val: TextJobParams) -> Self {
-        if !val.language.is_empty() {
-            let mut text_job = val.new_paragraph();
-            let value: Cow<'_, str> = val.value.into();
-            let language: Cow<'_, str> = val.language.into();
+        val.format().0
+    }
+}
+
+impl TextJobParams<'_> {
+    /// Formats the text, along with the
+    /// mistakes that were found in it
+    pub fn format(mut self) -> (SmallVec<[TextSection; 1]>, Vec<TextError>) {
+        if let Some(code) = self.code.take() {
+            (self.format_code(code), Vec::new())
+        } else if !self.language.is_empty() {
+            let mut text_job = self.new_paragraph();
+            let value: Cow<'_, str> = self.value.into();
+            let language: Cow<'_, str> = self.language.into();
             if !format_highlighted(
                 value.as_ref(),
                 language.as_ref(),
-                &val.default_attrs,
+                &self.default_attrs,
+                &self.code_theme,
                 &mut text_job,
             ) {
                 text_job
                     .rich_text
-                    .push((value.into(), val.default_attrs.clone()));
+                    .push((value.into(), self.default_attrs.clone()));
             }
-            if val.tagged {
+            if self.tagged {
                 text_job.tag = Some(TextTag::Code);
             } else {
                 text_job.tag = None;
             }
-            smallvec::smallvec![TextSection::Paragraph(text_job)]
+            (
+                smallvec::smallvec![TextSection::Paragraph(text_job)],
+                Vec::new(),
+            )
         } else {
-            val.format_djot()
+            self.format_djot()
         }
     }
-}
 
-impl TextJobParams<'_> {
     fn new_paragraph(&self) -> TextParagraph {
         TextParagraph {
             font_size: self.default_font_size,
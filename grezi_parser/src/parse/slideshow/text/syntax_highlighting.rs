@@ -3,11 +3,11 @@ use std::{cell::RefCell, collections::HashMap, ops::Deref, rc::Rc};
 use tree_sitter_highlight::{HighlightConfiguration, HighlightEvent};
 
 use crate::{
-    parse::helix_loader,
+    parse::helix_loader::{self, grammar::GrammarError},
     text::{Attrs, Modifier, TextParagraph},
 };
 
-use super::dark_plus_theme::THEME_COLORS;
+use super::code_theme::CodeTheme;
 
 macro_rules! get_highlight_config {
     ($language:expr) => {
@@ -18,7 +18,7 @@ macro_rules! get_highlight_config {
                     Ok(config) => config,
                     Err(e) => return Rc::new(Err(e)),
                 };
-                config.configure(super::dark_plus_theme::THEME_NAMES);
+                config.configure(super::code_theme::THEME_NAMES);
                 Rc::new(Ok(config))
             }))
         })
@@ -26,20 +26,27 @@ macro_rules! get_highlight_config {
 }
 
 thread_local! {
-    static HELIX_HIGHLIGHT_CONFIGS: RefCell<HashMap<smartstring::alias::String, Rc<Result<HighlightConfiguration, libloading::Error>>, ahash::RandomState>> = RefCell::new(HashMap::default());
+    static HELIX_HIGHLIGHT_CONFIGS: RefCell<HashMap<smartstring::alias::String, Rc<Result<HighlightConfiguration, GrammarError>>, ahash::RandomState>> = RefCell::new(HashMap::default());
+}
+
+/// Whether there is a grammar to highlight `language` with,
+/// either in the Helix runtime or bundled with the parser
+pub fn language_available(language: &str) -> bool {
+    get_highlight_config!(language).is_ok()
 }
 
 pub fn format_highlighted(
     value: &str,
     language: &str,
     default_attrs: &Attrs,
+    theme: &CodeTheme,
     paragraph: &mut TextParagraph,
 ) -> bool {
     let mut highlighter = tree_sitter_highlight::Highlighter::new();
     let initial_config = unsafe {
         std::mem::transmute::<
-            &Result<HighlightConfiguration, libloading::Error>,
-            &Result<HighlightConfiguration, libloading::Error>,
+            &Result<HighlightConfiguration, GrammarError>,
+            &Result<HighlightConfiguration, GrammarError>,
         >(get_highlight_config!(language).deref())
     };
     let Ok(initial_config) = initial_config else {
@@ -88,7 +95,7 @@ pub fn format_highlighted(
                 modifiers_active.pop();
             }
             HighlightEvent::HighlightStart(highlight) => {
-                modifiers_active.push(THEME_COLORS[highlight.0]);
+                modifiers_active.push(theme.modifiers(highlight.0));
             }
         }
     }
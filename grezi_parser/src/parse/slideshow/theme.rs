@@ -0,0 +1,185 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, LazyLock},
+    time::SystemTime,
+};
+
+use css_color::Srgb;
+use ecolor::Color32;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use smart_default::SmartDefault;
+
+use crate::{HIGHLIGHT_COLOR_DEFAULT, LINK_COLOR_DEFAULT, slide::BgColor, text::Attrs};
+
+use super::{
+    object::next_generation,
+    registers::bg_color_from_srgb,
+    text::code_theme::{CodeTheme, code_theme_table},
+};
+
+/// Themes are parsed again only when their file changes, so
+/// that objects and slides using them can be reused
+static THEMES: LazyLock<Mutex<HashMap<PathBuf, (SystemTime, Arc<Theme>)>>> =
+    LazyLock::new(Default::default);
+
+/// The look of a whole deck, selected with
+/// `<THEME: "corporate.toml">`. Registers that come
+/// after it, like `BACKGROUND`, still override it
+#[derive(SmartDefault, Debug)]
+pub struct Theme {
+    pub bg: BgColor,
+    /// What the text of every text object starts from
+    pub default_attrs: Attrs,
+    #[default = 64.0]
+    pub header_size: f32,
+    #[default = 48.0]
+    pub paragraph_size: f32,
+    /// How many times bigger than its text each level of
+    /// djot heading is
+    #[default([2.0, 1.5, 1.17, 1.0, 0.83, 0.67])]
+    pub heading_sizes: [f32; 6],
+    /// Color of `highlight` actions, and of `{=marked=}` djot
+    #[default(HIGHLIGHT_COLOR_DEFAULT)]
+    pub highlight: Color32,
+    /// Color of djot links
+    #[default(LINK_COLOR_DEFAULT)]
+    pub link: Color32,
+    pub code: CodeTheme,
+    #[default = 15.0]
+    pub margin: f32,
+    pub margin_per: f32,
+    /// Changes every time the theme file changes,
+    /// and is 0 for the default theme
+    pub generation: u64,
+}
+
+/// A theme file, written in TOML like
+///
+/// ```toml
+/// background = "#fafafa"
+/// highlight = "#ffd70060"
+/// link = "#0969da"
+/// margin = 20
+/// heading_sizes = [2.0, 1.5, 1.17, 1.0, 0.83, 0.67]
+///
+/// [text]
+/// font_family = "Inter:light"
+/// color = "#202020"
+/// header_size = 60
+///
+/// # A Helix theme, or a path to one
+/// code_theme = "light_plus"
+///
+/// # Changes to the scopes of the code theme
+/// [code]
+/// keyword = "#0000ff"
+/// comment = { fg = "#008000", modifiers = ["italic"] }
+/// ```
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    background: Option<String>,
+    highlight: Option<String>,
+    link: Option<String>,
+    margin: Option<f32>,
+    margin_per: Option<f32>,
+    heading_sizes: Option<[f32; 6]>,
+    text: TextThemeFile,
+    code_theme: Option<String>,
+    code: Option<toml::Table>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct TextThemeFile {
+    font_family: Option<String>,
+    color: Option<String>,
+    header_size: Option<f32>,
+    paragraph_size: Option<f32>,
+}
+
+/// Loads the theme at `path`, reusing the last one
+/// loaded from it if the file hasn't changed since
+pub fn load_theme(path: &Path) -> Result<Arc<Theme>, String> {
+    let modified = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|e| e.to_string())?;
+    if let Some((loaded, theme)) = THEMES.lock().get(path) {
+        if *loaded == modified {
+            return Ok(Arc::clone(theme));
+        }
+    }
+
+    let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let file: ThemeFile = toml::from_str(&source).map_err(|e| e.to_string())?;
+    let theme = Arc::new(Theme::from_file(file, path)?);
+    THEMES
+        .lock()
+        .insert(path.to_path_buf(), (modified, Arc::clone(&theme)));
+    Ok(theme)
+}
+
+impl Theme {
+    fn from_file(file: ThemeFile, path: &Path) -> Result<Self, String> {
+        let mut theme = Theme {
+            generation: next_generation(),
+            ..Default::default()
+        };
+        if let Some(background) = file.background {
+            theme.bg = bg_color_from_srgb(parse_srgb(&background)?);
+        }
+        if let Some(highlight) = file.highlight {
+            theme.highlight = parse_color(&highlight)?;
+        }
+        if let Some(link) = file.link {
+            theme.link = parse_color(&link)?;
+        }
+        if let Some(margin) = file.margin {
+            theme.margin = margin;
+        }
+        if let Some(margin_per) = file.margin_per {
+            theme.margin_per = margin_per;
+        }
+        if let Some(heading_sizes) = file.heading_sizes {
+            theme.heading_sizes = heading_sizes;
+        }
+        if let Some(font_family) = file.text.font_family {
+            theme.default_attrs.apply_fontstr(&font_family);
+        }
+        if let Some(color) = file.text.color {
+            theme.default_attrs.color = parse_color(&color)?;
+        }
+        if let Some(header_size) = file.text.header_size {
+            theme.header_size = header_size;
+        }
+        if let Some(paragraph_size) = file.text.paragraph_size {
+            theme.paragraph_size = paragraph_size;
+        }
+        if file.code_theme.is_some() || file.code.is_some() {
+            let mut code = match file.code_theme {
+                Some(code_theme) => code_theme_table(&code_theme, &path.to_string_lossy())?,
+                None => code_theme_table("dark_plus", "")?,
+            };
+            code.extend(file.code.unwrap_or_default());
+            theme.code = CodeTheme::from_helix(&code);
+        }
+        Ok(theme)
+    }
+}
+
+fn parse_srgb(color: &str) -> Result<Srgb, String> {
+    Srgb::from_str(color).map_err(|_| format!("`{}` is not a valid color", color))
+}
+
+fn parse_color(color: &str) -> Result<Color32, String> {
+    let c = parse_srgb(color)?;
+    Ok(Color32::from_rgba_unmultiplied(
+        (c.red * 255.0) as u8,
+        (c.green * 255.0) as u8,
+        (c.blue * 255.0) as u8,
+        (c.alpha * 255.0) as u8,
+    ))
+}
@@ -0,0 +1,106 @@
+use std::{borrow::Cow, collections::HashSet, io};
+
+use ropey::Rope;
+use tree_sitter::Node;
+use tree_sitter_grz::{FieldName, NodeKind};
+
+use crate::parse::{
+    CharRange,
+    cursor::{ErrorInfo, GrzCursor},
+    error::{ErrsWithSource, ParseError},
+};
+
+use super::style::declares_style;
+
+/// Warns about objects that are never placed on a slide, and
+/// viewboxes that are never referenced. This looks at the whole
+/// tree, so it stays correct when slides are reused
+pub fn report_unused_symbols(cursor: &GrzCursor, errors: &ErrsWithSource) -> io::Result<()> {
+    let ErrorInfo { source, tree } = cursor.error_info();
+    let root = tree.root_node();
+    // Names can't be trusted while the tree is broken
+    if root.has_error() {
+        return Ok(());
+    }
+
+    let mut objects = Vec::new();
+    let mut viewboxes = Vec::new();
+    let mut used_objects = HashSet::new();
+    let mut used_viewboxes = HashSet::new();
+
+    let mut root_cursor = root.walk();
+    for definition in root.named_children(&mut root_cursor) {
+        match NodeKind::from(definition.kind_id()) {
+            // Styles are used by name from strings,
+            // which can't be followed reliably
+            NodeKind::SymObj if !declares_style(definition, source) => {
+                objects.extend(definition.child_by_field_id(FieldName::FieldName as u16))
+            }
+            NodeKind::SymViewbox => {
+                viewboxes.extend(definition.child_by_field_id(FieldName::FieldName as u16))
+            }
+            _ => {}
+        }
+        find_references(definition, source, &mut used_objects, &mut used_viewboxes);
+    }
+
+    for (names, used, unused_error) in [
+        (
+            objects,
+            used_objects,
+            ParseError::UnusedObject as fn(CharRange, String) -> ParseError,
+        ),
+        (viewboxes, used_viewboxes, ParseError::UnusedViewbox),
+    ] {
+        for name in names {
+            let name_text: Cow<'_, str> = source.byte_slice(name.byte_range()).into();
+            if !used.contains(&name_text) {
+                errors.append_error(
+                    unused_error(
+                        CharRange::new(name.range(), source)?,
+                        name_text.into_owned(),
+                    ),
+                    ErrorInfo { source, tree },
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn find_references<'a>(
+    node: Node,
+    source: &'a Rope,
+    used_objects: &mut HashSet<Cow<'a, str>>,
+    used_viewboxes: &mut HashSet<Cow<'a, str>>,
+) {
+    match NodeKind::from(node.kind_id()) {
+        NodeKind::SymSlideObj => {
+            if let Some(object) = node.child_by_field_id(FieldName::FieldObject as u16) {
+                used_objects.insert(source.byte_slice(object.byte_range()).into());
+            }
+        }
+        // Objects that others derive from are used too
+        NodeKind::SymObjInner => {
+            if let Some(ty) = node.child_by_field_id(FieldName::FieldTy as u16) {
+                used_objects.insert(source.byte_slice(ty.byte_range()).into());
+            }
+        }
+        NodeKind::SymVbRef => {
+            if let Some(viewbox) = node
+                .child_by_field_id(FieldName::FieldViewbox as u16)
+                .filter(|v| NodeKind::from(v.kind_id()) == NodeKind::SymIdentifier)
+            {
+                used_viewboxes.insert(source.byte_slice(viewbox.byte_range()).into());
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        find_references(child, source, used_objects, used_viewboxes);
+    }
+}
@@ -19,6 +19,10 @@ pub struct Slide {
     /// interpolation looks better
     pub bg: BgColor,
     pub create_edges: bool,
+    #[serde(skip)]
+    #[cfg(feature = "parse")]
+    // Used to color highlights
+    pub theme: std::sync::Arc<crate::parse::slideshow::theme::Theme>,
 }
 
 impl Slide {
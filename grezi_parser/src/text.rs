@@ -16,19 +16,55 @@ pub enum TextTag {
     Code,
 }
 
-#[derive(SmartDefault, Serialize, Deserialize, Debug)]
+#[derive(SmartDefault, Serialize, Deserialize, Debug, Clone)]
 pub enum TextSection {
     #[default]
     Paragraph(TextParagraph),
     Blockquote(TextJob),
     List(Vec<(TextParagraph, TextJob)>),
+    /// The lines of a `Code` object, each with the line
+    /// number or diff sign that goes in front of it
+    Code(Vec<(Option<TextParagraph>, TextParagraph)>),
+    Table(TextTable),
 }
 
-#[derive(Default, Serialize, Deserialize, Debug)]
+/// A djot table, laid out as a grid
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
+pub struct TextTable {
+    pub rows: Vec<TableRow>,
+    /// How the cells of each column are aligned
+    pub alignments: Vec<Align>,
+    /// Color of the lines between rows, if they are drawn
+    pub rules: Option<Color32>,
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
+pub struct TableRow {
+    /// Whether the cells of this row are headers
+    pub head: bool,
+    pub cells: Vec<TextParagraph>,
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct TextParagraph {
     pub rich_text: SmallVec<[(smartstring::alias::String, Attrs); 1]>,
     pub font_size: f32,
     pub tag: Option<TextTag>,
+    /// Formulas in the text, each standing in for
+    /// the next span that is a `MATH_PLACEHOLDER`
+    pub math: Vec<Math>,
+}
+
+/// The text of a span that is a formula
+pub const MATH_PLACEHOLDER: &str = "\u{FFFC}";
+
+/// A formula, written in MathML
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
+pub struct Math {
+    pub mathml: Arc<str>,
+    /// Whether the formula goes on its own line,
+    /// with limits under and over big operators
+    pub display: bool,
 }
 
 #[derive(SmartDefault, Deserialize, Serialize, Debug, Clone)]
@@ -39,6 +75,17 @@ pub struct Attrs {
     pub stretch: Stretch,
     pub style: Style,
     pub weight: Weight,
+    /// Where the text links to, which is a URL,
+    /// or a slide when it starts with `#`
+    pub link: Option<Arc<str>>,
+    /// Draws a line under the text
+    pub underline: bool,
+    /// Draws a line through the text
+    pub strikethrough: bool,
+    pub baseline: Baseline,
+    /// The size of the text, when it isn't the
+    /// size of the rest of its paragraph
+    pub font_size: Option<f32>,
 }
 
 impl Attrs {
@@ -96,21 +143,32 @@ impl Attrs {
             Modifier::Style(style) => self.style = style,
             Modifier::Weight(weight) => self.weight = weight,
             Modifier::Color(color) => self.color = color,
-            Modifier::Strikethrough => {
-                // unimplemented
-            }
+            Modifier::Underline => self.underline = true,
+            Modifier::Strikethrough => self.strikethrough = true,
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum Modifier {
     Style(Style),
     Weight(Weight),
     Color(Color32),
+    Underline,
     Strikethrough,
 }
 
+/// Where text sits relative to the text around it
+#[derive(Default, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Baseline {
+    #[default]
+    Normal,
+    /// Lowered and smaller, like the 2 in H₂O
+    Subscript,
+    /// Raised and smaller, like the 2 in x²
+    Superscript,
+}
+
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub enum Family {
     Name(Arc<str>),
@@ -144,7 +202,7 @@ pub enum Style {
     Oblique,
 }
 
-#[derive(Default, Serialize, Deserialize, Debug)]
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum Align {
     #[default]
     Left,
@@ -154,7 +212,7 @@ pub enum Align {
     End,
 }
 
-#[derive(SmartDefault, Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(SmartDefault, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct Weight(#[default = 400] pub u16);
 
 impl Weight {
@@ -167,4 +225,21 @@ impl Weight {
     pub const BOLD: Weight = Weight(700);
     pub const EXTRA_BOLD: Weight = Weight(800);
     pub const BLACK: Weight = Weight(900);
+
+    /// Parses a weight written as a number, or by the
+    /// same names `Attrs::apply_fontstr` understands
+    pub fn from_name(name: &str) -> Option<Weight> {
+        match name {
+            "thin" => Some(Weight::THIN),
+            "extra_light" => Some(Weight::EXTRA_LIGHT),
+            "light" => Some(Weight::LIGHT),
+            "normal" => Some(Weight::NORMAL),
+            "medium" => Some(Weight::MEDIUM),
+            "semibold" => Some(Weight::SEMIBOLD),
+            "bold" => Some(Weight::BOLD),
+            "extra_bold" => Some(Weight::EXTRA_BOLD),
+            "black" => Some(Weight::BLACK),
+            number => number.parse().ok().map(Weight),
+        }
+    }
 }
@@ -24,6 +24,7 @@ impl eframe::App for App {
     fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
         egui::CentralPanel::default().show(ctx, |ui| {
             let max_rect = ui.max_rect();
+            let mut clicked_link = None;
 
             ctx.input(|input| {
                 for event in input.events.iter().map(|e| Cow::Borrowed(e)) {
@@ -31,9 +32,22 @@ impl eframe::App for App {
                         egui::Event::PointerButton {
                             pressed: true,
                             modifiers: Modifiers::NONE,
+                            pos,
                             ..
+                        } => {
+                            let link = self
+                                .resolved_slide
+                                .as_ref()
+                                .and_then(|resolved| resolved.link_at(max_rect, *pos));
+                            if link.is_some() {
+                                clicked_link = link;
+                            } else {
+                                self.slide_index += 1;
+                                self.time = input.time;
+                                self.resolved_slide = None;
+                            }
                         }
-                        | egui::Event::Key {
+                        egui::Event::Key {
                             key: egui::Key::ArrowRight | egui::Key::Space,
                             pressed: true,
                             ..
@@ -78,6 +92,29 @@ impl eframe::App for App {
                 }
             });
 
+            // Outside of `ctx.input`, which can't open URLs while it holds the input
+            if let Some(link) = clicked_link {
+                if link.starts_with('#') {
+                    let index = self
+                        .shared_data
+                        .slideshow
+                        .load()
+                        .deref()
+                        .as_ref()
+                        .and_then(|root| root.slide_index(&link));
+                    match index {
+                        Some(index) => {
+                            self.slide_index = index;
+                            self.time = ctx.input(|i| i.time);
+                            self.resolved_slide = None;
+                        }
+                        None => tracing::warn!("No slide is named `{}`", link),
+                    }
+                } else {
+                    ctx.open_url(egui::OpenUrl::new_tab(link));
+                }
+            }
+
             let mut buffers = Vec::new();
 
             if let Some(ref root) = *self.shared_data.slideshow.load() {